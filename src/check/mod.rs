@@ -0,0 +1,5 @@
+pub mod report;
+pub mod site_checker;
+
+pub use report::ReportFormat;
+pub use site_checker::SiteChecker;
@@ -0,0 +1,115 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+
+/// Output format of `dodge check`
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ReportFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    BrokenLink,
+    BrokenFragment,
+    MissingAsset,
+    DuplicateId,
+    EmptyTitle,
+    OrphanPage,
+    BrokenExternal,
+}
+
+/// A problem found in one generated page
+#[derive(Debug, Serialize)]
+pub struct Issue {
+    pub severity: Severity,
+    pub kind: IssueKind,
+    /// Site-relative URL of the page the problem was found in
+    pub page: String,
+    pub message: String,
+}
+
+/// An external URL and the pages linking to it
+#[derive(Debug, Serialize)]
+pub struct ExternalUrl {
+    pub url: String,
+    pub pages: Vec<String>,
+    /// Result from the configured check endpoint, absent when none is configured
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct CheckReport {
+    pub pages_checked: usize,
+    pub issues: Vec<Issue>,
+    pub external: Vec<ExternalUrl>,
+}
+
+impl CheckReport {
+    pub fn push(&mut self, severity: Severity, kind: IssueKind, page: &str, message: String) {
+        self.issues.push(Issue {
+            severity,
+            kind,
+            page: page.to_string(),
+            message,
+        });
+    }
+
+    pub fn count(&self, severity: Severity) -> usize {
+        self.issues.iter().filter(|issue| issue.severity == severity).count()
+    }
+
+    /// Process exit code for `dodge check`: errors fail CI, warnings such as orphan pages are
+    /// informational
+    pub fn exit_code(&self) -> i32 {
+        if self.count(Severity::Error) > 0 {
+            1
+        } else {
+            0
+        }
+    }
+
+    pub fn render(&self, format: ReportFormat) -> Result<String> {
+        match format {
+            ReportFormat::Json => Ok(serde_json::to_string_pretty(self)?),
+            ReportFormat::Text => Ok(self.render_text()),
+        }
+    }
+
+    fn render_text(&self) -> String {
+        let mut out = format!("🔍 Checked {} pages\n", self.pages_checked);
+
+        for issue in &self.issues {
+            let icon = match issue.severity {
+                Severity::Error => "❌",
+                Severity::Warning => "⚠️ ",
+            };
+            out.push_str(&format!("{} {}: {}\n", icon, issue.page, issue.message));
+        }
+
+        if !self.external.is_empty() {
+            out.push_str(&format!("🌐 External URLs ({}):\n", self.external.len()));
+            for external in &self.external {
+                let status = external.status.as_deref().map(|status| format!(" [{}]", status)).unwrap_or_default();
+                out.push_str(&format!("   {} ({} page(s)){}\n", external.url, external.pages.len(), status));
+            }
+        }
+
+        out.push_str(&format!(
+            "📋 {} error(s), {} warning(s)\n",
+            self.count(Severity::Error),
+            self.count(Severity::Warning)
+        ));
+        out
+    }
+}
@@ -0,0 +1,347 @@
+use anyhow::Result;
+use glob::glob;
+use scraper::{Html, Selector};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use super::report::{CheckReport, ExternalUrl, IssueKind, Severity};
+use crate::config::Config;
+
+/// How a page refers to another URL
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ReferenceKind {
+    /// Navigation a reader can follow (`<a href>`, `<form action>`)
+    Link,
+    /// Metadata links such as canonical and feed discovery
+    Meta,
+    /// Something the page loads: images, stylesheets, scripts, media
+    Asset,
+}
+
+/// What a reference points at once resolved against the page
+enum Target {
+    /// A file in the output directory: its site-relative URL and optional fragment
+    Local(String, Option<String>),
+    External(String),
+    /// `mailto:`, `data:` and other URLs that can't be checked
+    Ignored,
+}
+
+/// A generated HTML page and everything it refers to
+struct Document {
+    ids: HashSet<String>,
+    references: Vec<(ReferenceKind, String)>,
+}
+
+/// Crawls a generated site for broken links and HTML problems
+pub struct SiteChecker {
+    output_dir: String,
+}
+
+impl SiteChecker {
+    pub fn new(output_dir: String) -> Self {
+        Self { output_dir }
+    }
+
+    pub fn check(&self, config: &Config) -> Result<CheckReport> {
+        let mut report = CheckReport::default();
+        let documents = self.load_documents(&mut report)?;
+        report.pages_checked = documents.len();
+
+        let mut inbound: HashMap<&str, usize> = HashMap::new();
+        let mut external: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+
+        for (url, document) in &documents {
+            for (kind, reference) in &document.references {
+                match self.resolve(reference, url, &config.base_url) {
+                    Target::Ignored => {}
+                    Target::External(external_url) => {
+                        external.entry(external_url).or_default().insert(url.clone());
+                    }
+                    Target::Local(path, fragment) => {
+                        let Some(file) = self.find_file(&path) else {
+                            let (issue, noun) = match kind {
+                                ReferenceKind::Asset => (IssueKind::MissingAsset, "missing asset"),
+                                _ => (IssueKind::BrokenLink, "broken link to"),
+                            };
+                            report.push(Severity::Error, issue, url, format!("{} {}", noun, reference));
+                            continue;
+                        };
+
+                        let Some((target_url, target)) = documents.get_key_value(&file) else {
+                            continue;
+                        };
+                        if *kind == ReferenceKind::Link && target_url != url {
+                            *inbound.entry(target_url.as_str()).or_default() += 1;
+                        }
+                        if let Some(fragment) = fragment.filter(|fragment| !fragment.is_empty() && fragment != "top")
+                            && !target.ids.contains(&fragment)
+                        {
+                            report.push(
+                                Severity::Error,
+                                IssueKind::BrokenFragment,
+                                url,
+                                format!("link to missing anchor {}", reference),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        // Pages nothing links to, apart from the site's entry points
+        for url in documents.keys() {
+            let entry_point = url == "/index.html" || url == "/404.html";
+            if !entry_point && !inbound.contains_key(url.as_str()) {
+                report.push(Severity::Warning, IssueKind::OrphanPage, url, "orphan page, no other page links to it".to_string());
+            }
+        }
+
+        report.external = external
+            .into_iter()
+            .map(|(url, pages)| ExternalUrl { url, pages: pages.into_iter().collect(), status: None })
+            .collect();
+        if let Some(endpoint) = &config.check.external_endpoint {
+            Self::check_external(&mut report, endpoint, config.check.timeout_secs);
+        }
+
+        Ok(report)
+    }
+
+    /// Parse every HTML file in the output directory, reporting per-page problems on the way
+    fn load_documents(&self, report: &mut CheckReport) -> Result<BTreeMap<String, Document>> {
+        let title = Selector::parse("title").expect("valid selector");
+        let with_id = Selector::parse("[id], a[name]").expect("valid selector");
+        let references = Selector::parse(
+            "a[href], form[action], link[href], img[src], img[srcset], source[src], source[srcset], \
+             script[src], audio[src], video[src], video[poster], meta[property='og:image']",
+        )
+        .expect("valid selector");
+
+        let mut documents = BTreeMap::new();
+        let pattern = format!("{}/**/*.html", self.output_dir);
+        for entry in glob(&pattern)? {
+            let path = entry?;
+            let url = self.url_for(&path);
+            let html = Html::parse_document(&fs::read_to_string(&path)?);
+
+            if html.select(&title).next().is_none_or(|title| title.text().collect::<String>().trim().is_empty()) {
+                report.push(Severity::Error, IssueKind::EmptyTitle, &url, "missing or empty <title>".to_string());
+            }
+
+            let mut ids = HashSet::new();
+            for element in html.select(&with_id) {
+                if let Some(id) = element.attr("id")
+                    && !ids.insert(id.to_string())
+                {
+                    report.push(Severity::Error, IssueKind::DuplicateId, &url, format!("duplicate id \"{}\"", id));
+                }
+                if let Some(name) = element.attr("name") {
+                    ids.insert(name.to_string());
+                }
+            }
+
+            let mut document_references = Vec::new();
+            for element in html.select(&references) {
+                let element = element.value();
+                let kind = match element.name() {
+                    "a" | "form" => ReferenceKind::Link,
+                    "link" if element.attr("rel").is_some_and(|rel| rel.contains("stylesheet") || rel.contains("icon")) => {
+                        ReferenceKind::Asset
+                    }
+                    "link" => ReferenceKind::Meta,
+                    _ => ReferenceKind::Asset,
+                };
+                for attribute in ["href", "action", "src", "poster", "content"] {
+                    if let Some(value) = element.attr(attribute) {
+                        document_references.push((kind, value.to_string()));
+                    }
+                }
+                // Each srcset candidate is "url [descriptor]"
+                if let Some(srcset) = element.attr("srcset") {
+                    for candidate in srcset.split(',') {
+                        if let Some(candidate_url) = candidate.split_whitespace().next() {
+                            document_references.push((kind, candidate_url.to_string()));
+                        }
+                    }
+                }
+            }
+
+            documents.insert(url, Document { ids, references: document_references });
+        }
+
+        Ok(documents)
+    }
+
+    /// Site-relative URL of a file in the output directory
+    fn url_for(&self, path: &Path) -> String {
+        let relative = path.strip_prefix(&self.output_dir).unwrap_or(path);
+        let parts: Vec<String> = relative.components().map(|part| part.as_os_str().to_string_lossy().into_owned()).collect();
+        format!("/{}", parts.join("/"))
+    }
+
+    /// Classify a reference and, for local ones, resolve it to a site-relative path
+    fn resolve(&self, reference: &str, page_url: &str, base_url: &str) -> Target {
+        let reference = reference.trim();
+        let base_url = base_url.trim_end_matches('/');
+
+        let local = if let Some(rest) = reference.strip_prefix(base_url).filter(|_| !base_url.is_empty()) {
+            if !rest.is_empty() && !rest.starts_with(['/', '#', '?']) {
+                return Target::External(reference.to_string());
+            }
+            rest
+        } else if reference.starts_with("//") {
+            return Target::External(format!("https:{}", reference));
+        } else if reference.starts_with("http://") || reference.starts_with("https://") {
+            return Target::External(reference.to_string());
+        } else if reference.split(['/', '?', '#']).next().is_some_and(|scheme| scheme.contains(':')) {
+            return Target::Ignored;
+        } else {
+            reference
+        };
+
+        let (path, fragment) = match local.split_once('#') {
+            Some((path, fragment)) => (path, Some(fragment.to_string())),
+            None => (local, None),
+        };
+        let path = path.split('?').next().unwrap_or("");
+
+        let absolute = if path.is_empty() {
+            page_url.to_string()
+        } else if path.starts_with('/') {
+            path.to_string()
+        } else {
+            let directory = page_url.rsplit_once('/').map_or("", |(directory, _)| directory);
+            format!("{}/{}", directory, path)
+        };
+
+        Target::Local(Self::normalize(&absolute), fragment)
+    }
+
+    /// Collapse `.` and `..` segments of a site-relative path
+    fn normalize(path: &str) -> String {
+        let mut segments: Vec<&str> = Vec::new();
+        for segment in path.split('/') {
+            match segment {
+                "" | "." => {}
+                ".." => {
+                    segments.pop();
+                }
+                other => segments.push(other),
+            }
+        }
+        let trailing = if path.ends_with('/') && !segments.is_empty() { "/" } else { "" };
+        format!("/{}{}", segments.join("/"), trailing)
+    }
+
+    /// Site-relative URL of the file a path is served from, if it exists
+    fn find_file(&self, path: &str) -> Option<String> {
+        let on_disk = format!("{}{}", self.output_dir, path);
+        if Path::new(&on_disk).is_file() {
+            return Some(path.to_string());
+        }
+        let index = format!("{}/index.html", path.trim_end_matches('/'));
+        Path::new(&format!("{}{}", self.output_dir, index)).is_file().then_some(index)
+    }
+
+    /// Ask the configured endpoint about every external URL, `{url}` being replaced by the
+    /// percent-encoded URL. Anything but a 2xx response marks the URL as broken.
+    fn check_external(report: &mut CheckReport, endpoint: &str, timeout_secs: u64) {
+        let agent: ureq::Agent = ureq::Agent::config_builder()
+            .timeout_global(Some(Duration::from_secs(timeout_secs)))
+            .build()
+            .into();
+
+        let mut broken = Vec::new();
+        for external in &mut report.external {
+            let request_url = endpoint.replace("{url}", &Self::percent_encode(&external.url));
+            let status = match agent.get(&request_url).call() {
+                Ok(response) => format!("ok {}", response.status().as_u16()),
+                Err(ureq::Error::StatusCode(code)) => format!("broken {}", code),
+                Err(error) => format!("error: {}", error),
+            };
+            if !status.starts_with("ok") {
+                for page in &external.pages {
+                    broken.push((page.clone(), format!("external link {} is {}", external.url, status)));
+                }
+            }
+            external.status = Some(status);
+        }
+
+        for (page, message) in broken {
+            report.push(Severity::Error, IssueKind::BrokenExternal, &page, message);
+        }
+    }
+
+    fn percent_encode(text: &str) -> String {
+        text.bytes()
+            .map(|byte| match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+                _ => format!("%{:02X}", byte),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::check::report::ReportFormat;
+    use crate::utils::TestDir;
+
+    /// Kinds of the issues reported for one page
+    fn kinds(report: &CheckReport, page: &str) -> Vec<IssueKind> {
+        report.issues.iter().filter(|issue| issue.page == page).map(|issue| issue.kind).collect()
+    }
+
+    fn check(dir: &TestDir) -> CheckReport {
+        SiteChecker::new(dir.path().to_string_lossy().into_owned()).check(&Config::default()).unwrap()
+    }
+
+    #[test]
+    fn reports_each_kind_of_problem() {
+        let dir = TestDir::new("check");
+        dir.write(
+            "index.html",
+            r##"<html><head><title>Home</title></head><body>
+            <a href="/a.html">a</a> <a href="a.html#intro">intro</a> <a href="/a.html#gone">gone</a>
+            <a href="/missing.html">missing</a> <img src="/logo.png"> <a href="https://other.example/">out</a>
+            </body></html>"##,
+        );
+        dir.write("a.html", r#"<html><head><title>A</title></head><body><h2 id="intro">Intro</h2></body></html>"#);
+        dir.write("b.html", r#"<html><head><title> </title></head><body><p id="x"></p><p id="x"></p></body></html>"#);
+
+        let report = check(&dir);
+        assert_eq!(report.pages_checked, 3);
+        assert_eq!(
+            kinds(&report, "/index.html"),
+            [IssueKind::BrokenFragment, IssueKind::BrokenLink, IssueKind::MissingAsset]
+        );
+        assert!(kinds(&report, "/a.html").is_empty());
+        assert_eq!(kinds(&report, "/b.html"), [IssueKind::EmptyTitle, IssueKind::DuplicateId, IssueKind::OrphanPage]);
+        assert_eq!(report.external[0].url, "https://other.example/");
+        assert_eq!(report.external[0].pages, ["/index.html"]);
+    }
+
+    #[test]
+    fn json_report_counts_issues_and_sets_the_exit_code() {
+        let dir = TestDir::new("check-json");
+        dir.write("index.html", r#"<html><head><title>Home</title></head><body><a href="/missing.html">x</a></body></html>"#);
+        dir.write("orphan.html", r#"<html><head><title>Orphan</title></head><body></body></html>"#);
+
+        let report = check(&dir);
+        let json: serde_json::Value = serde_json::from_str(&report.render(ReportFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["pages_checked"], 2);
+        let severities: Vec<&str> = json["issues"].as_array().unwrap().iter().map(|issue| issue["severity"].as_str().unwrap()).collect();
+        assert_eq!(severities, ["error", "warning"]);
+        assert_eq!(report.exit_code(), 1);
+
+        // Warnings alone don't fail the check
+        dir.write("index.html", r#"<html><head><title>Home</title></head><body></body></html>"#);
+        let report = check(&dir);
+        assert_eq!((report.count(Severity::Error), report.count(Severity::Warning)), (0, 1));
+        assert_eq!(report.exit_code(), 0);
+    }
+}
@@ -1,4 +1,5 @@
 use clap::{Parser, Subcommand};
+use crate::check::ReportFormat;
 
 #[derive(Parser)]
 #[command(name = "dodge")]
@@ -24,6 +25,10 @@ pub enum Commands {
         /// Clean output directory before building
         #[arg(long)]
         clean: bool,
+
+        /// Minify HTML, CSS and JS output
+        #[arg(long)]
+        minify: bool,
     },
     /// Serve the static site with a development server
     Serve {
@@ -46,6 +51,28 @@ pub enum Commands {
         /// Input directory for auto-build
         #[arg(long, default_value = "content")]
         input: String,
+
+        /// Rebuild when content, Sass, themes, static files or config.toml change (implies --build)
+        #[arg(long)]
+        watch: bool,
+    },
+    /// Check the generated site for broken links, missing assets and HTML problems
+    Check {
+        /// Input directory containing markdown files
+        #[arg(short, long, default_value = "content")]
+        input: String,
+
+        /// Output directory to build into and check
+        #[arg(short, long, default_value = "public")]
+        output: String,
+
+        /// Check the existing output directory without building first
+        #[arg(long)]
+        no_build: bool,
+
+        /// Report format, written to stdout; build progress goes to stderr so JSON can be piped
+        #[arg(long, value_enum, default_value = "text")]
+        format: ReportFormat,
     },
     /// Clean the output directory
     Clean {
@@ -1,8 +1,10 @@
 use anyhow::Result;
+use crate::check::{ReportFormat, SiteChecker};
 use crate::generator::SiteGenerator;
 use crate::theme::Theme;
 use crate::config::Config;
 use crate::server::DevServer;
+use crate::watcher::SiteWatcher;
 use super::Commands;
 
 pub struct CommandHandler;
@@ -10,11 +12,14 @@ pub struct CommandHandler;
 impl CommandHandler {
     pub async fn handle_command(command: Commands) -> Result<()> {
         match command {
-            Commands::Build { input, output, clean } => {
-                Self::handle_build(input, output, clean).await
+            Commands::Build { input, output, clean, minify } => {
+                Self::handle_build(input, output, clean, minify).await
             }
-            Commands::Serve { dir, port, host, build, input } => {
-                Self::handle_serve(dir, port, host, build, input).await
+            Commands::Serve { dir, port, host, build, input, watch } => {
+                Self::handle_serve(dir, port, host, build, input, watch).await
+            }
+            Commands::Check { input, output, no_build, format } => {
+                Self::handle_check(input, output, no_build, format).await
             }
             Commands::Clean { output } => {
                 Self::handle_clean(output).await
@@ -22,8 +27,9 @@ impl CommandHandler {
         }
     }
 
-    async fn handle_build(input: String, output: String, clean: bool) -> Result<()> {
-        let config = Config::load().unwrap_or_default();
+    async fn handle_build(input: String, output: String, clean: bool, minify: bool) -> Result<()> {
+        let mut config = Config::load().unwrap_or_default();
+        config.build.minify |= minify;
         let theme = config.theme.parse::<Theme>().unwrap_or(Theme::Hacker);
         let generator = SiteGenerator::new(input, output);
         
@@ -31,23 +37,71 @@ impl CommandHandler {
             generator.clean()?;
         }
         
-        generator.build_with_config(&config.blog_title, &theme)?;
+        generator.build_with_config(&config, &theme)?;
         Ok(())
     }
 
-    async fn handle_serve(dir: String, port: u16, host: String, build: bool, input: String) -> Result<()> {
-        if build {
+    async fn handle_serve(dir: String, port: u16, host: String, build: bool, input: String, watch: bool) -> Result<()> {
+        if build || watch {
             println!("🔨 Auto-building site before serving...");
-            let generator = SiteGenerator::new(input, dir.clone());
-            generator.build()?;
+            Self::build_site(&input, &dir)?;
             println!();
         }
+
+        if watch {
+            let build = Config::load().unwrap_or_default().build;
+            let watcher = SiteWatcher::new(vec![
+                input.clone(),
+                build.static_dir,
+                build.sass_dir,
+                build.theme_dir,
+                "config.toml".to_string(),
+            ]);
+            let output = dir.clone();
+            std::thread::spawn(move || {
+                watcher.watch(|| {
+                    println!("🔄 Change detected, rebuilding...");
+                    // Keep serving the last good build; the error says which file to fix
+                    if let Err(error) = Self::build_site(&input, &output) {
+                        eprintln!("❌ Build failed: {:#}", error);
+                    }
+                })
+            });
+        }
         
         let server = DevServer::new(dir, port, host);
         server.start().await?;
         Ok(())
     }
 
+    /// Build with the current config.toml, reloaded so watch-mode rebuilds pick up edits to it
+    fn build_site(input: &str, output: &str) -> Result<()> {
+        let config = Config::load().unwrap_or_default();
+        let theme = config.theme.parse::<Theme>().unwrap_or(Theme::Hacker);
+        SiteGenerator::new(input.to_string(), output.to_string()).build_with_config(&config, &theme)
+    }
+
+    async fn handle_check(input: String, output: String, no_build: bool, format: ReportFormat) -> Result<()> {
+        let config = Config::load().unwrap_or_default();
+
+        if !no_build {
+            let theme = config.theme.parse::<Theme>().unwrap_or(Theme::Hacker);
+            // Keep stdout for the report, so `--format json` can be piped
+            let mut generator = SiteGenerator::new(input, output.clone());
+            generator.progress_to_stderr = true;
+            generator.build_with_config(&config, &theme)?;
+            eprintln!();
+        }
+
+        let report = SiteChecker::new(output).check(&config)?;
+        println!("{}", report.render(format)?);
+
+        if report.exit_code() != 0 {
+            std::process::exit(report.exit_code());
+        }
+        Ok(())
+    }
+
     async fn handle_clean(output: String) -> Result<()> {
         let generator = SiteGenerator::new("".to_string(), output);
         generator.clean()?;
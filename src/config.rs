@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 
@@ -6,6 +7,295 @@ use std::path::Path;
 pub struct Config {
     pub blog_title: String,
     pub theme: String,
+    /// Public URL the site is deployed at, used for absolute links
+    #[serde(default = "default_base_url")]
+    pub base_url: String,
+    #[serde(default = "default_description")]
+    pub description: String,
+    #[serde(default = "default_language")]
+    pub language: String,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub build: BuildConfig,
+    #[serde(default)]
+    pub toc: TocConfig,
+    #[serde(default)]
+    pub anchors: AnchorConfig,
+    #[serde(default)]
+    pub summary: SummaryConfig,
+    #[serde(default)]
+    pub reading: ReadingConfig,
+    #[serde(default)]
+    pub feed: FeedConfig,
+    #[serde(default)]
+    pub podcast: PodcastConfig,
+    #[serde(default)]
+    pub sitemap: SitemapConfig,
+    #[serde(default)]
+    pub robots: RobotsConfig,
+    #[serde(default)]
+    pub seo: SeoConfig,
+    #[serde(default)]
+    pub social_cards: SocialCardConfig,
+    #[serde(default)]
+    pub search: SearchConfig,
+    #[serde(default)]
+    pub images: ImageConfig,
+    #[serde(default)]
+    pub figures: FigureConfig,
+    #[serde(default)]
+    pub css: CssConfig,
+    #[serde(default)]
+    pub links: LinkConfig,
+    #[serde(default)]
+    pub check: CheckConfig,
+    #[serde(default)]
+    pub theme_toggle: ThemeToggleConfig,
+    /// Values for the options declared in the theme's manifest, e.g. `accent_color = "#ff0080"`
+    #[serde(default)]
+    pub theme_options: BTreeMap<String, toml::Value>,
+}
+
+/// Build settings
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct BuildConfig {
+    /// Directory copied verbatim into the output (skipped when missing)
+    pub static_dir: String,
+    /// Sass entry points compiled to `/assets/` and linked from every page (skipped when missing)
+    pub sass_dir: String,
+    /// Site-local theme overrides; `{theme_dir}/{theme}/style.scss` replaces the built-in stylesheet
+    pub theme_dir: String,
+    /// Minify the generated HTML, CSS and JS (also enabled by `dodge build --minify`)
+    pub minify: bool,
+    /// Add content hashes to `/assets/` file names, with SRI and an `assets-manifest.json`
+    pub fingerprint: bool,
+}
+
+/// Table of contents settings
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct TocConfig {
+    /// Show a table of contents on pages that don't set `toc` in front matter
+    pub enabled: bool,
+    /// Shallowest heading level included
+    pub min_depth: u8,
+    /// Deepest heading level included
+    pub max_depth: u8,
+}
+
+/// Heading anchor settings
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct AnchorConfig {
+    /// Add a "§" permalink next to each heading
+    pub permalinks: bool,
+}
+
+/// Page summary settings
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct SummaryConfig {
+    /// Number of words used when a page has no description or `<!-- more -->` marker
+    pub words: usize,
+}
+
+/// Word count and reading time settings
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct ReadingConfig {
+    pub words_per_minute: usize,
+    /// Show word count and reading time in the post header
+    pub show: bool,
+}
+
+/// Feed settings
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct FeedConfig {
+    /// Maximum number of entries, newest first (0 for no limit)
+    pub limit: usize,
+    /// Embed full post content instead of the summary
+    pub full_content: bool,
+    /// Feed formats to generate
+    pub formats: Vec<FeedFormat>,
+    /// Content sections (top-level directories) whose pages are published in feeds.
+    /// Each also gets its own feed under `/<section>/`.
+    pub sections: Vec<String>,
+    /// Generate a feed per tag under `/tags/<tag>/`
+    pub tag_feeds: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedFormat {
+    Rss,
+    Atom,
+    Json,
+}
+
+/// Podcast feed settings
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct PodcastConfig {
+    /// Content section whose pages are episodes; its RSS feed becomes the podcast feed
+    pub section: Option<String>,
+    /// Cover artwork, a site-relative path or absolute URL (at least 1400x1400)
+    pub image: Option<String>,
+    /// Apple Podcasts category, e.g. "Technology"
+    pub category: Option<String>,
+    pub explicit: bool,
+    /// Contact shown to podcast directories
+    pub owner_name: Option<String>,
+    pub owner_email: Option<String>,
+}
+
+/// Sitemap settings
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct SitemapConfig {
+    /// Write `sitemap.xml` listing every page without `sitemap: false`
+    pub enabled: bool,
+}
+
+/// robots.txt settings
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct RobotsConfig {
+    /// Write `robots.txt`
+    pub enabled: bool,
+    pub user_agent: String,
+    /// Site-relative paths crawlers may not visit
+    pub disallow: Vec<String>,
+    /// Extra lines appended verbatim, e.g. rules for other user agents
+    pub extra: Option<String>,
+}
+
+/// Search engine and social sharing metadata settings
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct SeoConfig {
+    /// Default share image for pages without an `image` in front matter
+    pub image: Option<String>,
+    /// Twitter/X handle of the site, e.g. "@dodge"
+    pub twitter_site: Option<String>,
+    /// Twitter/X handle of the author
+    pub twitter_creator: Option<String>,
+}
+
+/// Generated social card image settings
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct SocialCardConfig {
+    /// Render a PNG card per page, used as `og:image` when the page sets no `image`
+    pub enabled: bool,
+    /// Directory rendered cards are kept in between builds
+    pub cache_dir: String,
+}
+
+/// Client-side search settings
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct SearchConfig {
+    /// Write a search index and a `/search/` page
+    pub enabled: bool,
+    /// Most distinct body terms indexed per page (0 for no limit)
+    pub max_terms: usize,
+}
+
+/// Reader-facing switch between themes or color schemes
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct ThemeToggleConfig {
+    /// Variants readers can switch between, as `theme` or `theme:scheme`, e.g.
+    /// `["vercel:light", "vercel:dark"]` or `["vercel", "hacker"]`. Needs at least two.
+    pub variants: Vec<String>,
+}
+
+/// Responsive image settings
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct ImageConfig {
+    /// Resize local markdown images and wrap them in `<picture>`
+    pub enabled: bool,
+    /// Widths in pixels to generate, skipping any wider than the original
+    pub widths: Vec<u32>,
+    /// Modern formats offered as `<source>` elements, in order of preference. WebP copies are
+    /// lossless, so they are only offered for PNG and WebP originals.
+    pub formats: Vec<ImageFormat>,
+    /// Encoder quality from 1 to 100 for JPEG and AVIF
+    pub quality: u8,
+    /// Default `sizes` attribute telling the browser how wide the image is displayed
+    pub sizes: String,
+    /// Directory encoded variants are kept in between builds
+    pub cache_dir: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    Avif,
+    Webp,
+    Jpeg,
+    Png,
+}
+
+/// Stylesheet optimization settings
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct CssConfig {
+    /// Drop rules of the theme and site stylesheets whose selectors match nothing in the
+    /// generated HTML
+    pub remove_unused: bool,
+    /// Class names, IDs and element names to keep even when no page uses them. Names in HTML
+    /// strings, `createElement` and `classList.add` calls of the scripts a page loads are found
+    /// on their own; list names scripts build any other way. A trailing `*` matches any suffix
+    /// (`"toast-*"`).
+    pub safelist: Vec<String>,
+    /// Inline the rules each page uses into its `<head>` and load the stylesheet without
+    /// blocking rendering
+    pub inline_critical: bool,
+}
+
+/// Captioned figure settings
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct FigureConfig {
+    /// Number figures in document order ("Figure 1", "Figure 2", ...)
+    pub numbering: bool,
+    /// Word used for figure numbers and cross-reference text
+    pub label: String,
+}
+
+/// Internal link settings
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct LinkConfig {
+    /// What to do about links to missing pages, files or anchors
+    pub on_missing: LinkPolicy,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkPolicy {
+    /// Fail the build
+    #[default]
+    Error,
+    /// Print a warning and keep building
+    Warn,
+}
+
+/// `dodge check` settings
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct CheckConfig {
+    /// Link checking service for external URLs, e.g. "https://checker.example/?url={url}".
+    /// `{url}` is replaced by the percent-encoded URL and a non-2xx response marks it broken.
+    /// When unset, external URLs are only listed.
+    pub external_endpoint: Option<String>,
+    /// Timeout per external check
+    pub timeout_secs: u64,
 }
 
 impl Default for Config {
@@ -13,14 +303,184 @@ impl Default for Config {
         Config {
             blog_title: "Dodge SSG".to_string(),
             theme: "hacker".to_string(),
+            base_url: default_base_url(),
+            description: default_description(),
+            language: default_language(),
+            author: None,
+            build: BuildConfig::default(),
+            toc: TocConfig::default(),
+            anchors: AnchorConfig::default(),
+            summary: SummaryConfig::default(),
+            reading: ReadingConfig::default(),
+            feed: FeedConfig::default(),
+            podcast: PodcastConfig::default(),
+            sitemap: SitemapConfig::default(),
+            robots: RobotsConfig::default(),
+            seo: SeoConfig::default(),
+            social_cards: SocialCardConfig::default(),
+            search: SearchConfig::default(),
+            images: ImageConfig::default(),
+            figures: FigureConfig::default(),
+            css: CssConfig::default(),
+            links: LinkConfig::default(),
+            check: CheckConfig::default(),
+            theme_toggle: ThemeToggleConfig::default(),
+            theme_options: BTreeMap::new(),
+        }
+    }
+}
+
+impl Default for BuildConfig {
+    fn default() -> Self {
+        BuildConfig {
+            static_dir: "static".to_string(),
+            sass_dir: "sass".to_string(),
+            theme_dir: "themes".to_string(),
+            minify: false,
+            fingerprint: false,
+        }
+    }
+}
+
+impl Default for TocConfig {
+    fn default() -> Self {
+        TocConfig {
+            enabled: false,
+            min_depth: 2,
+            max_depth: 3,
+        }
+    }
+}
+
+impl Default for SummaryConfig {
+    fn default() -> Self {
+        SummaryConfig { words: 50 }
+    }
+}
+
+impl Default for ReadingConfig {
+    fn default() -> Self {
+        ReadingConfig {
+            words_per_minute: 200,
+            show: true,
+        }
+    }
+}
+
+impl Default for FeedConfig {
+    fn default() -> Self {
+        FeedConfig {
+            limit: 20,
+            full_content: false,
+            formats: vec![FeedFormat::Rss],
+            sections: vec!["posts".to_string()],
+            tag_feeds: true,
+        }
+    }
+}
+
+impl Default for SitemapConfig {
+    fn default() -> Self {
+        SitemapConfig { enabled: true }
+    }
+}
+
+impl Default for RobotsConfig {
+    fn default() -> Self {
+        RobotsConfig {
+            enabled: true,
+            user_agent: "*".to_string(),
+            disallow: Vec::new(),
+            extra: None,
+        }
+    }
+}
+
+impl Default for SocialCardConfig {
+    fn default() -> Self {
+        SocialCardConfig {
+            enabled: true,
+            cache_dir: ".dodge-cache/social-cards".to_string(),
+        }
+    }
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        SearchConfig {
+            enabled: true,
+            max_terms: 500,
+        }
+    }
+}
+
+impl Default for ImageConfig {
+    fn default() -> Self {
+        ImageConfig {
+            enabled: true,
+            widths: vec![480, 960, 1440],
+            formats: vec![ImageFormat::Avif, ImageFormat::Webp],
+            quality: 75,
+            sizes: "(max-width: 768px) 100vw, 768px".to_string(),
+            cache_dir: ".dodge-cache/images".to_string(),
+        }
+    }
+}
+
+impl Default for FigureConfig {
+    fn default() -> Self {
+        FigureConfig {
+            numbering: false,
+            label: "Figure".to_string(),
+        }
+    }
+}
+
+impl Default for CheckConfig {
+    fn default() -> Self {
+        CheckConfig {
+            external_endpoint: None,
+            timeout_secs: 10,
+        }
+    }
+}
+
+impl FeedFormat {
+    /// File name the feed is written to
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            FeedFormat::Rss => "rss.xml",
+            FeedFormat::Atom => "atom.xml",
+            FeedFormat::Json => "feed.json",
+        }
+    }
+
+    /// MIME type used for `<link rel="alternate">` discovery
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            FeedFormat::Rss => "application/rss+xml",
+            FeedFormat::Atom => "application/atom+xml",
+            FeedFormat::Json => "application/feed+json",
         }
     }
 }
 
+fn default_base_url() -> String {
+    "http://localhost:3000".to_string()
+}
+
+fn default_description() -> String {
+    "A blog powered by Dodge SSG".to_string()
+}
+
+fn default_language() -> String {
+    "en".to_string()
+}
+
 impl Config {
     pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
         let config_path = Path::new("config.toml");
-        
+
         if config_path.exists() {
             let config_content = fs::read_to_string(config_path)?;
             let config: Config = toml::from_str(&config_content)?;
@@ -33,4 +493,45 @@ impl Config {
             Ok(default_config)
         }
     }
-}
\ No newline at end of file
+
+    /// Turn a site-relative path into an absolute URL using `base_url`.
+    /// Full `http(s)://` URLs are returned unchanged.
+    pub fn absolute_url(&self, path: &str) -> String {
+        if path.starts_with("http://") || path.starts_with("https://") {
+            return path.to_string();
+        }
+        format!("{}/{}", self.base_url.trim_end_matches('/'), path.trim_start_matches('/'))
+    }
+}
+
+impl ImageFormat {
+    /// Format of a source image, judged by its file extension
+    pub fn from_extension(extension: &str) -> Option<ImageFormat> {
+        match extension.to_ascii_lowercase().as_str() {
+            "avif" => Some(ImageFormat::Avif),
+            "webp" => Some(ImageFormat::Webp),
+            "jpg" | "jpeg" => Some(ImageFormat::Jpeg),
+            "png" => Some(ImageFormat::Png),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Avif => "avif",
+            ImageFormat::Webp => "webp",
+            ImageFormat::Jpeg => "jpg",
+            ImageFormat::Png => "png",
+        }
+    }
+
+    /// MIME type used for `<source type>`
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ImageFormat::Avif => "image/avif",
+            ImageFormat::Webp => "image/webp",
+            ImageFormat::Jpeg => "image/jpeg",
+            ImageFormat::Png => "image/png",
+        }
+    }
+}
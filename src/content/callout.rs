@@ -0,0 +1,99 @@
+use comrak::nodes::{AlertType, AstNode, NodeAlert, NodeValue};
+use comrak::Arena;
+
+use crate::utils::HtmlUtils;
+
+pub struct CalloutRenderer;
+
+impl CalloutRenderer {
+    /// Replace GitHub-style alert blockquotes (`> [!NOTE]`) with callout `<aside>` markup
+    pub fn transform<'a>(arena: &'a Arena<AstNode<'a>>, root: &'a AstNode<'a>) {
+        let alerts: Vec<_> = root
+            .descendants()
+            .filter(|node| matches!(node.data.borrow().value, NodeValue::Alert(_)))
+            .collect();
+
+        for node in alerts {
+            let alert = match &node.data.borrow().value {
+                NodeValue::Alert(alert) => alert.clone(),
+                _ => continue,
+            };
+            let (open, close) = Self::render_wrapper(&alert);
+
+            node.insert_before(arena.alloc(NodeValue::Raw(open).into()));
+            while let Some(child) = node.first_child() {
+                child.detach();
+                node.insert_before(child);
+            }
+            node.insert_before(arena.alloc(NodeValue::Raw(close).into()));
+            node.detach();
+        }
+    }
+
+    /// Build the opening and closing HTML that wraps the callout body
+    fn render_wrapper(alert: &NodeAlert) -> (String, String) {
+        let kind = Self::kind(alert.alert_type);
+        let icon = Self::icon(alert.alert_type);
+
+        // A leading `-` or `+` after the marker makes the callout collapsible,
+        // collapsed or expanded by default respectively (`> [!TIP]- Title`)
+        let raw_title = alert.title.clone().unwrap_or_default();
+        let (collapse, title) = match raw_title.chars().next() {
+            Some(marker @ ('-' | '+')) => (Some(marker == '+'), raw_title[1..].trim().to_string()),
+            _ => (None, raw_title),
+        };
+        let title = if title.is_empty() {
+            alert.alert_type.default_title()
+        } else {
+            title
+        };
+
+        let title_html = format!(
+            r#"<span class="callout-icon" aria-hidden="true">{}</span><span class="callout-label">{}</span>"#,
+            icon,
+            HtmlUtils::escape(&title)
+        );
+
+        match collapse {
+            None => (
+                format!(
+                    "<aside class=\"callout callout-{}\" data-callout=\"{}\">\n<p class=\"callout-title\">{}</p>\n<div class=\"callout-body\">\n",
+                    kind, kind, title_html
+                ),
+                "</div>\n</aside>\n".to_string(),
+            ),
+            Some(open) => (
+                format!(
+                    "<aside class=\"callout callout-{} callout-collapsible\" data-callout=\"{}\">\n<details{}>\n<summary class=\"callout-title\">{}</summary>\n<div class=\"callout-body\">\n",
+                    kind,
+                    kind,
+                    if open { " open" } else { "" },
+                    title_html
+                ),
+                "</div>\n</details>\n</aside>\n".to_string(),
+            ),
+        }
+    }
+
+    /// CSS modifier used for each callout type
+    fn kind(alert_type: AlertType) -> &'static str {
+        match alert_type {
+            AlertType::Note => "note",
+            AlertType::Tip => "tip",
+            AlertType::Important => "important",
+            AlertType::Warning => "warning",
+            AlertType::Caution => "caution",
+        }
+    }
+
+    /// Icon shown before the callout title
+    fn icon(alert_type: AlertType) -> &'static str {
+        match alert_type {
+            AlertType::Note => "ℹ",
+            AlertType::Tip => "💡",
+            AlertType::Important => "❗",
+            AlertType::Warning => "⚠",
+            AlertType::Caution => "⛔",
+        }
+    }
+}
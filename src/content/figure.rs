@@ -0,0 +1,181 @@
+use comrak::nodes::{AstNode, NodeValue};
+use comrak::Arena;
+use std::collections::HashMap;
+
+use super::images::ResponsiveImages;
+use super::markdown::MarkdownProcessor;
+use crate::config::Config;
+use crate::utils::HtmlUtils;
+
+/// A captioned image rendered as `<figure>`
+#[derive(Debug, Clone)]
+pub struct Figure {
+    /// Label set with a trailing `{#label}` in the caption, used as the element ID
+    pub id: Option<String>,
+    /// Position among the document's figures, starting at 1
+    pub number: usize,
+    pub caption: String,
+}
+
+pub struct FigureRenderer;
+
+impl FigureRenderer {
+    /// Wrap paragraphs holding nothing but a captioned image in `<figure>`. The caption is the
+    /// image title (`![alt](src "Caption {#label}")`) or an image shortcode's `caption`
+    /// argument. Empty links to a label (`[](#label)`) get the figure number, or the caption
+    /// without numbering, as their text. Images without alt text are reported.
+    pub fn transform<'a>(
+        arena: &'a Arena<AstNode<'a>>,
+        root: &'a AstNode<'a>,
+        source_path: &str,
+        config: &Config,
+    ) -> Vec<Figure> {
+        Self::warn_missing_alt(root, source_path);
+
+        let paragraphs: Vec<_> = root
+            .descendants()
+            .filter(|node| matches!(node.data.borrow().value, NodeValue::Paragraph))
+            .collect();
+
+        let mut figures = Vec::new();
+        for paragraph in paragraphs {
+            let shortcode = ResponsiveImages::shortcode_arguments(&MarkdownProcessor::text_content(paragraph));
+            let (caption, id) = match &shortcode {
+                Some(arguments) => match arguments.get("caption") {
+                    Some(caption) => Self::split_label(caption),
+                    None => continue,
+                },
+                None => match Self::take_caption(paragraph) {
+                    Some(title) => Self::split_label(&title),
+                    None => continue,
+                },
+            };
+
+            let figure = Figure { id, number: figures.len() + 1, caption };
+            let (open, close) = Self::render_wrapper(&figure, config);
+            paragraph.insert_before(arena.alloc(NodeValue::Raw(open).into()));
+            paragraph.insert_after(arena.alloc(NodeValue::Raw(close).into()));
+            // Shortcodes are replaced as a whole later; a plain image leaves its `<p>` behind
+            if shortcode.is_none() {
+                while let Some(child) = paragraph.first_child() {
+                    child.detach();
+                    paragraph.insert_before(child);
+                }
+                paragraph.detach();
+            }
+            figures.push(figure);
+        }
+
+        Self::fill_references(arena, root, &figures, config);
+        figures
+    }
+
+    fn warn_missing_alt<'a>(root: &'a AstNode<'a>, source_path: &str) {
+        for node in root.descendants() {
+            let src = match &node.data.borrow().value {
+                NodeValue::Image(link) if MarkdownProcessor::text_content(node).trim().is_empty() => link.url.clone(),
+                NodeValue::Paragraph => {
+                    match ResponsiveImages::shortcode_arguments(&MarkdownProcessor::text_content(node)) {
+                        Some(arguments) if arguments.get("alt").is_none_or(|alt| alt.trim().is_empty()) => {
+                            arguments.get("src").cloned().unwrap_or_default()
+                        }
+                        _ => continue,
+                    }
+                }
+                _ => continue,
+            };
+            eprintln!("⚠️  Image without alt text in {}: {}", source_path, src);
+        }
+    }
+
+    /// Take the title of the only image in a paragraph (optionally inside a link), clearing
+    /// it so it isn't repeated as a tooltip
+    fn take_caption<'a>(paragraph: &'a AstNode<'a>) -> Option<String> {
+        let mut content = paragraph.children().filter(|child| match &child.data.borrow().value {
+            NodeValue::Text(text) => !text.trim().is_empty(),
+            NodeValue::SoftBreak | NodeValue::LineBreak => false,
+            _ => true,
+        });
+        let only = content.next().filter(|_| content.next().is_none())?;
+
+        let image = if matches!(only.data.borrow().value, NodeValue::Link(_)) {
+            only.first_child().filter(|child| child.next_sibling().is_none())?
+        } else {
+            only
+        };
+
+        let mut data = image.data.borrow_mut();
+        let NodeValue::Image(link) = &mut data.value else {
+            return None;
+        };
+        if link.title.trim().is_empty() {
+            return None;
+        }
+        Some(std::mem::take(&mut link.title))
+    }
+
+    /// Split `Caption {#label}` into the caption and its label
+    fn split_label(caption: &str) -> (String, Option<String>) {
+        let caption = caption.trim();
+        if let Some(start) = caption.rfind("{#")
+            && let Some(label) = caption[start + 2..].strip_suffix('}')
+            && !label.is_empty()
+            && !label.contains(|c: char| c.is_whitespace() || c == '{' || c == '}')
+        {
+            return (caption[..start].trim_end().to_string(), Some(label.to_string()));
+        }
+        (caption.to_string(), None)
+    }
+
+    fn render_wrapper(figure: &Figure, config: &Config) -> (String, String) {
+        let open = match &figure.id {
+            Some(id) => format!("<figure class=\"figure\" id=\"{}\">\n", HtmlUtils::escape(id)),
+            None => "<figure class=\"figure\">\n".to_string(),
+        };
+
+        let number = if config.figures.numbering {
+            let separator = if figure.caption.is_empty() { "" } else { ":" };
+            format!(
+                "<span class=\"figure-number\">{} {}{}</span> ",
+                HtmlUtils::escape(&config.figures.label),
+                figure.number,
+                separator
+            )
+        } else {
+            String::new()
+        };
+        let close = format!(
+            "\n<figcaption>{}{}</figcaption>\n</figure>\n",
+            number,
+            HtmlUtils::escape(&figure.caption)
+        );
+
+        (open, close)
+    }
+
+    /// Give empty same-page links to a figure label their text
+    fn fill_references<'a>(arena: &'a Arena<AstNode<'a>>, root: &'a AstNode<'a>, figures: &[Figure], config: &Config) {
+        let labels: HashMap<&str, &Figure> = figures
+            .iter()
+            .filter_map(|figure| figure.id.as_deref().map(|id| (id, figure)))
+            .collect();
+
+        let references: Vec<_> = root
+            .descendants()
+            .filter(|node| node.first_child().is_none())
+            .filter_map(|node| match &node.data.borrow().value {
+                NodeValue::Link(link) => link.url.strip_prefix('#').and_then(|id| labels.get(id)).map(|figure| (node, *figure)),
+                _ => None,
+            })
+            .collect();
+
+        for (node, figure) in references {
+            let text = if config.figures.numbering || figure.caption.is_empty() {
+                format!("{} {}", config.figures.label, figure.number)
+            } else {
+                figure.caption.clone()
+            };
+            node.append(arena.alloc(NodeValue::Text(text).into()));
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use anyhow::Result;
+use serde::{Deserialize, Deserializer};
+
+/// Optional YAML metadata block at the top of a markdown file
+#[derive(Debug, Default, Deserialize, Clone)]
+#[serde(default)]
+pub struct FrontMatter {
+    pub title: Option<String>,
+    /// Overrides the generated summary in listings, feeds and meta tags
+    pub description: Option<String>,
+    /// Share image for Open Graph and Twitter cards, relative to the page or site-relative
+    pub image: Option<String>,
+    /// Publication date (`2025-09-16` or RFC 3339)
+    pub date: Option<String>,
+    /// Last modification date, same formats as `date`
+    pub updated: Option<String>,
+    pub tags: Vec<String>,
+    pub toc: Option<bool>,
+    /// Set to `false` to leave the page out of `sitemap.xml`
+    pub sitemap: Option<bool>,
+    /// Set to `false` to leave the page out of the search index
+    pub search: Option<bool>,
+    /// Podcast episode audio file, local path or URL
+    pub audio: Option<String>,
+    /// Episode length as `HH:MM:SS` or seconds
+    #[serde(deserialize_with = "string_or_number")]
+    pub duration: Option<String>,
+    pub episode: Option<u32>,
+    pub season: Option<u32>,
+    /// Page template (`page`, `landing`), overriding the section default
+    pub layout: Option<String>,
+    /// Theme for this page instead of the site theme (`vercel`, `hacker`)
+    pub theme: Option<String>,
+    /// In a section's `_index.md`: defaults for every page in the directory and below it
+    pub cascade: PageDefaults,
+}
+
+/// Front-matter keys a section's `_index.md` can set for the pages under it
+#[derive(Debug, Default, Deserialize, Clone)]
+#[serde(default)]
+pub struct PageDefaults {
+    pub layout: Option<String>,
+    pub theme: Option<String>,
+}
+
+impl PageDefaults {
+    /// Fill unset keys from `parent`, the defaults of an enclosing section
+    pub fn or(self, parent: &PageDefaults) -> PageDefaults {
+        PageDefaults {
+            layout: self.layout.or_else(|| parent.layout.clone()),
+            theme: self.theme.or_else(|| parent.theme.clone()),
+        }
+    }
+}
+
+impl FrontMatter {
+    /// Split a markdown file into its front matter and body
+    pub fn parse(content: &str) -> Result<(FrontMatter, &str)> {
+        let Some(rest) = content
+            .strip_prefix("---\n")
+            .or_else(|| content.strip_prefix("---\r\n"))
+        else {
+            return Ok((FrontMatter::default(), content));
+        };
+
+        // Find the closing delimiter on a line of its own
+        let mut offset = 0;
+        for line in rest.split_inclusive('\n') {
+            if line.trim_end() == "---" {
+                let yaml = &rest[..offset];
+                let body = &rest[offset + line.len()..];
+                let front_matter = if yaml.trim().is_empty() {
+                    FrontMatter::default()
+                } else {
+                    serde_yaml::from_str(yaml)?
+                };
+                return Ok((front_matter, body));
+            }
+            offset += line.len();
+        }
+
+        // No closing delimiter: treat the whole file as markdown
+        Ok((FrontMatter::default(), content))
+    }
+}
+
+/// Accept either a string or a bare number, e.g. `duration: 1830`
+fn string_or_number<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match Option::<serde_yaml::Value>::deserialize(deserializer)? {
+        Some(serde_yaml::Value::String(text)) => Some(text),
+        Some(serde_yaml::Value::Number(number)) => Some(number.to_string()),
+        _ => None,
+    })
+}
@@ -0,0 +1,174 @@
+use comrak::adapters::{HeadingAdapter, HeadingMeta};
+use comrak::nodes::{AstNode, NodeValue, Sourcepos};
+use std::collections::{HashSet, VecDeque};
+use std::io::{self, Write};
+use std::sync::Mutex;
+
+use super::MarkdownProcessor;
+use crate::utils::{HtmlUtils, SlugGenerator};
+
+/// A heading found in a rendered document
+#[derive(Debug, Clone)]
+pub struct Heading {
+    pub level: u8,
+    pub id: String,
+    pub title: String,
+}
+
+pub struct HeadingCollector;
+
+impl HeadingCollector {
+    /// Collect all headings in document order and assign each a unique ID.
+    /// A trailing `{#custom-id}` in the heading text sets the ID explicitly and is
+    /// removed from the rendered heading. An explicit ID used again gets a `-1`, `-2`, ...
+    /// suffix like generated ones; the returned warnings say which were renamed.
+    pub fn collect<'a>(root: &'a AstNode<'a>) -> (Vec<Heading>, Vec<String>) {
+        let nodes: Vec<_> = root
+            .descendants()
+            .filter_map(|node| match node.data.borrow().value {
+                NodeValue::Heading(ref heading) => Some((node, heading.level)),
+                _ => None,
+            })
+            .collect();
+
+        let mut custom_ids: Vec<_> = nodes.iter().map(|(node, _)| Self::take_custom_id(node)).collect();
+
+        // Reserve explicit IDs first so generated ones never collide with them
+        let mut used = HashSet::new();
+        let mut repeated = Vec::new();
+        for (index, id) in custom_ids.iter().enumerate() {
+            if let Some(id) = id
+                && !used.insert(id.clone())
+            {
+                repeated.push(index);
+            }
+        }
+        let mut warnings = Vec::new();
+        for index in repeated {
+            if let Some(id) = &mut custom_ids[index] {
+                let renamed = Self::unique(id, &mut used);
+                warnings.push(format!("Duplicate heading ID #{}, renamed to #{}", id, renamed));
+                *id = renamed;
+            }
+        }
+
+        let headings = nodes
+            .into_iter()
+            .zip(custom_ids)
+            .map(|((node, level), custom_id)| {
+                let title = MarkdownProcessor::text_content(node).trim().to_string();
+                let id = custom_id.unwrap_or_else(|| Self::unique_id(&title, &mut used));
+                Heading { level, id, title }
+            })
+            .collect();
+        (headings, warnings)
+    }
+
+    /// Strip a trailing `{#id}` from a heading and return the ID
+    fn take_custom_id<'a>(heading: &'a AstNode<'a>) -> Option<String> {
+        let last = heading.last_child()?;
+        let mut data = last.data.borrow_mut();
+        let NodeValue::Text(ref mut text) = data.value else {
+            return None;
+        };
+
+        let trimmed = text.trim_end();
+        let start = trimmed.rfind("{#")?;
+        let id = trimmed[start + 2..].strip_suffix('}')?;
+        if id.is_empty() || id.contains(|c: char| c.is_whitespace() || c == '{' || c == '}') {
+            return None;
+        }
+
+        let id = id.to_string();
+        text.truncate(start);
+        let kept = text.trim_end().len();
+        text.truncate(kept);
+        let now_empty = text.is_empty();
+        drop(data);
+
+        if now_empty {
+            last.detach();
+        }
+        Some(id)
+    }
+
+    /// Slugify a heading title, appending `-1`, `-2`, ... until it is unique
+    fn unique_id(title: &str, used: &mut HashSet<String>) -> String {
+        let base = SlugGenerator::slugify(title);
+        Self::unique(if base.is_empty() { "section" } else { &base }, used)
+    }
+
+    /// `base`, or `base` with the first `-1`, `-2`, ... suffix not in `used`, which is then reserved
+    fn unique(base: &str, used: &mut HashSet<String>) -> String {
+        let mut id = base.to_string();
+        let mut suffix = 0;
+        while used.contains(&id) {
+            suffix += 1;
+            id = format!("{}-{}", base, suffix);
+        }
+        used.insert(id.clone());
+        id
+    }
+}
+
+/// Renders heading tags with the IDs assigned by `HeadingCollector`
+pub struct HeadingRenderer {
+    ids: Mutex<VecDeque<String>>,
+    current: Mutex<Option<String>>,
+    permalinks: bool,
+}
+
+impl HeadingRenderer {
+    pub fn new(headings: &[Heading], permalinks: bool) -> Self {
+        Self {
+            ids: Mutex::new(headings.iter().map(|h| h.id.clone()).collect()),
+            current: Mutex::new(None),
+            permalinks,
+        }
+    }
+}
+
+impl HeadingAdapter for HeadingRenderer {
+    fn enter(&self, output: &mut dyn Write, heading: &HeadingMeta, _sourcepos: Option<Sourcepos>) -> io::Result<()> {
+        // Headings are rendered in the same order they were collected
+        let id = self.ids.lock().ok().and_then(|mut ids| ids.pop_front());
+        match &id {
+            Some(id) => write!(output, "<h{} id=\"{}\">", heading.level, HtmlUtils::escape(id))?,
+            None => write!(output, "<h{}>", heading.level)?,
+        }
+        if let Ok(mut current) = self.current.lock() {
+            *current = id;
+        }
+        Ok(())
+    }
+
+    fn exit(&self, output: &mut dyn Write, heading: &HeadingMeta) -> io::Result<()> {
+        let id = self.current.lock().ok().and_then(|mut current| current.take());
+        if let (true, Some(id)) = (self.permalinks, id) {
+            write!(
+                output,
+                "<a class=\"heading-anchor\" href=\"#{}\" aria-label=\"Permalink to this section\">§</a>",
+                HtmlUtils::escape(&id)
+            )?;
+        }
+        writeln!(output, "</h{}>", heading.level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use comrak::{parse_document, Arena, ComrakOptions};
+
+    #[test]
+    fn repeated_custom_ids_are_renamed() {
+        let arena = Arena::new();
+        let root = parse_document(&arena, "# A {#intro}\n\n## B {#intro}\n\n## Intro\n\n## C {#intro-1}", &ComrakOptions::default());
+
+        let (headings, warnings) = HeadingCollector::collect(root);
+        let ids: Vec<&str> = headings.iter().map(|heading| heading.id.as_str()).collect();
+        assert_eq!(ids, ["intro", "intro-2", "intro-3", "intro-1"]);
+        assert_eq!(warnings, ["Duplicate heading ID #intro, renamed to #intro-2"]);
+        assert_eq!(headings[1].title, "B");
+    }
+}
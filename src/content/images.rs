@@ -0,0 +1,318 @@
+use comrak::nodes::{AstNode, NodeValue};
+use comrak::Arena;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::links::LinkResolver;
+use super::markdown::MarkdownProcessor;
+use crate::config::{Config, ImageFormat};
+use crate::utils::{HashUtils, HtmlUtils};
+
+/// One resized, re-encoded copy of a source image, written by the site generator
+#[derive(Debug, Clone)]
+pub struct ImageVariant {
+    /// Source image on disk
+    pub source: PathBuf,
+    /// Site-relative URL the variant is written to
+    pub url: String,
+    pub width: u32,
+    pub height: u32,
+    pub format: ImageFormat,
+    /// Encoder quality from 1 to 100, `None` to encode losslessly
+    pub quality: Option<u8>,
+    /// File name of the variant in the image cache, unique per source content and settings
+    pub cache_name: String,
+}
+
+/// A local image found in markdown, resolved to a file
+struct SourceImage {
+    path: PathBuf,
+    /// Site-relative URL the original is copied to
+    url: String,
+    width: u32,
+    height: u32,
+    format: ImageFormat,
+    /// Content hash, so edited images get new variant names
+    hash: String,
+}
+
+/// Attributes of an image as written by the author
+struct ImageRequest {
+    src: String,
+    alt: String,
+    title: Option<String>,
+    sizes: Option<String>,
+}
+
+pub struct ResponsiveImages;
+
+impl ResponsiveImages {
+    /// Turn local markdown images and `{{ image(src="…", alt="…") }}` shortcodes into
+    /// `<picture>` elements with resized AVIF/WebP sources. Returns the variants the
+    /// markup refers to; encoding them is left to the site generator.
+    pub fn transform<'a>(
+        arena: &'a Arena<AstNode<'a>>,
+        root: &'a AstNode<'a>,
+        source_path: &str,
+        input_dir: &str,
+        config: &Config,
+    ) -> Vec<ImageVariant> {
+        let mut variants = Vec::new();
+
+        // Shortcodes stand alone in their paragraph and render as block-level markup
+        let shortcodes: Vec<_> = root
+            .descendants()
+            .filter(|node| matches!(node.data.borrow().value, NodeValue::Paragraph))
+            .filter_map(|node| Self::parse_shortcode(&MarkdownProcessor::text_content(node)).map(|request| (node, request)))
+            .collect();
+
+        for (node, request) in shortcodes {
+            let Some(request) = request else {
+                eprintln!("⚠️  Image shortcode without src in {}", source_path);
+                continue;
+            };
+            let html = match Self::resolve(&request.src, source_path, input_dir, config) {
+                Some(image) => Self::render(&image, &request, config, &mut variants),
+                None => {
+                    eprintln!("⚠️  Image not found in {}: {}", source_path, request.src);
+                    Self::render_img(&request.src, None, &request, None)
+                }
+            };
+            node.insert_before(arena.alloc(NodeValue::Raw(html).into()));
+            node.detach();
+        }
+
+        if !config.images.enabled {
+            return variants;
+        }
+
+        let images: Vec<_> = root
+            .descendants()
+            .filter(|node| matches!(node.data.borrow().value, NodeValue::Image(_)))
+            .collect();
+
+        for node in images {
+            let request = match &node.data.borrow().value {
+                NodeValue::Image(link) => ImageRequest {
+                    src: link.url.clone(),
+                    alt: MarkdownProcessor::text_content(node),
+                    title: Some(link.title.clone()).filter(|title| !title.is_empty()),
+                    sizes: None,
+                },
+                _ => continue,
+            };
+            // Remote and missing images are left as they are
+            let Some(image) = Self::resolve(&request.src, source_path, input_dir, config) else {
+                continue;
+            };
+
+            node.insert_before(arena.alloc(NodeValue::Raw(Self::render(&image, &request, config, &mut variants)).into()));
+            node.detach();
+        }
+
+        variants
+    }
+
+    /// Parse `{{ image(key="value", …) }}`. `Some(None)` is a shortcode missing its `src`.
+    fn parse_shortcode(text: &str) -> Option<Option<ImageRequest>> {
+        let mut arguments = Self::shortcode_arguments(text)?;
+        Some(arguments.remove("src").map(|src| ImageRequest {
+            src,
+            alt: arguments.remove("alt").unwrap_or_default(),
+            title: arguments.remove("title"),
+            sizes: arguments.remove("sizes"),
+        }))
+    }
+
+    /// Arguments of an image shortcode, `None` when `text` isn't one
+    pub(super) fn shortcode_arguments(text: &str) -> Option<HashMap<String, String>> {
+        let arguments = text
+            .trim()
+            .strip_prefix("{{")?
+            .strip_suffix("}}")?
+            .trim()
+            .strip_prefix("image(")?
+            .strip_suffix(')')?;
+
+        let mut values = HashMap::new();
+        let mut rest = arguments.trim();
+        while !rest.is_empty() {
+            let (key, after) = rest.split_once('=')?;
+            let after = after.trim_start().strip_prefix('"')?;
+            let (value, after) = after.split_once('"')?;
+            values.insert(key.trim().to_string(), value.to_string());
+            rest = after.trim_start().trim_start_matches(',').trim_start();
+        }
+        Some(values)
+    }
+
+    /// Find the file behind an image URL: `@/` and `/` paths are relative to the input
+    /// directory (or, for `/`, the static directory), anything else to the page
+    fn resolve(src: &str, source_path: &str, input_dir: &str, config: &Config) -> Option<SourceImage> {
+        if src.contains("://") || src.starts_with("//") || src.starts_with("data:") {
+            return None;
+        }
+        let src = src.split(['?', '#']).next().unwrap_or("");
+
+        let (path, url) = if let Some(rooted) = src.strip_prefix("@/").or_else(|| src.strip_prefix('/')) {
+            let in_content = Path::new(input_dir).join(rooted);
+            let path = if in_content.is_file() || src.starts_with("@/") {
+                in_content
+            } else {
+                Path::new(&config.build.static_dir).join(rooted)
+            };
+            (path, format!("/{}", rooted))
+        } else {
+            let path = LinkResolver::normalize(&Path::new(source_path).parent().unwrap_or(Path::new("")).join(src));
+            let relative = path.strip_prefix(LinkResolver::normalize(Path::new(input_dir))).ok()?;
+            let parts: Vec<String> = relative.components().map(|part| part.as_os_str().to_string_lossy().into_owned()).collect();
+            let url = format!("/{}", parts.join("/"));
+            (path, url)
+        };
+
+        // AVIF sources can't be decoded without native libraries
+        let format = ImageFormat::from_extension(&path.extension()?.to_string_lossy())
+            .filter(|format| *format != ImageFormat::Avif)?;
+        let (width, height) = image::image_dimensions(&path).ok()?;
+        let hash = HashUtils::sha256_hex(&fs::read(&path).ok()?);
+
+        Some(SourceImage { path, url, width, height, format, hash })
+    }
+
+    /// `<picture>` markup for an image, adding the variants it refers to
+    fn render(image: &SourceImage, request: &ImageRequest, config: &Config, variants: &mut Vec<ImageVariant>) -> String {
+        if !config.images.enabled {
+            return Self::render_img(&image.url, None, request, Some((image.width, image.height)));
+        }
+
+        let mut widths: Vec<u32> = config.images.widths.iter().copied().filter(|width| *width < image.width).collect();
+        widths.sort_unstable();
+        widths.dedup();
+
+        let sizes = request.sizes.as_deref().unwrap_or(&config.images.sizes);
+        let mut sources = String::new();
+        let offered = config.images.formats.iter().filter(|format| Self::offers(image.format, **format));
+        for format in offered {
+            // Modern formats get a full-width copy too, the original covers the fallback
+            let all_widths = widths.iter().copied().chain(std::iter::once(image.width));
+            let srcset = Self::add_variants(image, all_widths, *format, config, variants);
+            sources.push_str(&format!(
+                r#"<source type="{}" srcset="{}" sizes="{}">"#,
+                format.mime_type(),
+                srcset,
+                HtmlUtils::escape(sizes)
+            ));
+        }
+
+        let mut fallback = Self::add_variants(image, widths.iter().copied(), image.format, config, variants);
+        if !fallback.is_empty() {
+            fallback.push_str(", ");
+        }
+        fallback.push_str(&format!("{} {}w", image.url, image.width));
+
+        let img = Self::render_img(&image.url, Some((&fallback, sizes)), request, Some((image.width, image.height)));
+        format!("<picture>{}{}</picture>", sources, img)
+    }
+
+    fn render_img(src: &str, srcset: Option<(&str, &str)>, request: &ImageRequest, dimensions: Option<(u32, u32)>) -> String {
+        let mut img = format!(r#"<img src="{}""#, HtmlUtils::escape(src));
+        if let Some((srcset, sizes)) = srcset {
+            img.push_str(&format!(r#" srcset="{}" sizes="{}""#, HtmlUtils::escape(srcset), HtmlUtils::escape(sizes)));
+        }
+        if let Some((width, height)) = dimensions {
+            img.push_str(&format!(r#" width="{}" height="{}""#, width, height));
+        }
+        img.push_str(&format!(r#" alt="{}""#, HtmlUtils::escape(&request.alt)));
+        if let Some(title) = &request.title {
+            img.push_str(&format!(r#" title="{}""#, HtmlUtils::escape(title)));
+        }
+        img.push_str(r#" loading="lazy" decoding="async">"#);
+        img
+    }
+
+    /// Whether a `<source>` in `format` is worth offering for an original in `source`. WebP is
+    /// only encoded losslessly, which makes copies of photos larger than the JPEG they replace.
+    fn offers(source: ImageFormat, format: ImageFormat) -> bool {
+        match format {
+            _ if format == source => false,
+            ImageFormat::Webp => matches!(source, ImageFormat::Png | ImageFormat::Webp),
+            _ => true,
+        }
+    }
+
+    /// Quality to encode a copy of an image in `format` with, `None` for the lossless formats
+    fn quality(format: ImageFormat, config: &Config) -> Option<u8> {
+        match format {
+            ImageFormat::Png | ImageFormat::Webp => None,
+            ImageFormat::Avif | ImageFormat::Jpeg => Some(config.images.quality),
+        }
+    }
+
+    /// Record one variant per width and return the matching `srcset` value
+    fn add_variants(
+        image: &SourceImage,
+        widths: impl Iterator<Item = u32>,
+        format: ImageFormat,
+        config: &Config,
+        variants: &mut Vec<ImageVariant>,
+    ) -> String {
+        let directory = image.url.rsplit_once('/').map_or("", |(directory, _)| directory);
+        let stem = image.path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+
+        let quality = Self::quality(format, config);
+        let quality_tag = quality.map_or("lossless".to_string(), |quality| format!("q{}", quality));
+
+        let mut srcset = Vec::new();
+        for width in widths {
+            let height = ((image.height as u64 * width as u64) / image.width as u64).max(1) as u32;
+            let cache_name = format!("{}-{}-{}.{}", &image.hash[..16], width, quality_tag, format.extension());
+            let url = format!("{}/{}-{}w.{}.{}", directory, stem, width, &image.hash[..8], format.extension());
+            srcset.push(format!("{} {}w", url, width));
+            variants.push(ImageVariant {
+                source: image.path.clone(),
+                url,
+                width,
+                height,
+                format,
+                quality,
+                cache_name,
+            });
+        }
+        srcset.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TestDir;
+    use comrak::{parse_document, ComrakOptions};
+    use image::RgbImage;
+
+    #[test]
+    fn photos_get_avif_and_graphics_lossless_webp() {
+        let dir = TestDir::new("responsive-images");
+        let image = RgbImage::from_fn(640, 480, |x, y| image::Rgb([x as u8, y as u8, 128]));
+        image.save(dir.path().join("photo.jpg")).unwrap();
+        image.save(dir.path().join("chart.png")).unwrap();
+        let source_path = dir.write("page.md", "");
+        let input_dir = dir.path().to_string_lossy().into_owned();
+
+        let arena = Arena::new();
+        let root = parse_document(&arena, "![a](photo.jpg)\n\n![b](chart.png)", &ComrakOptions::default());
+        let variants = ResponsiveImages::transform(&arena, root, &source_path, &input_dir, &Config::default());
+
+        let copies = |original: &str| -> Vec<(ImageFormat, Option<u8>)> {
+            let mut copies: Vec<_> = variants
+                .iter()
+                .filter(|variant| variant.source.ends_with(original) && variant.width == 640)
+                .map(|variant| (variant.format, variant.quality))
+                .collect();
+            copies.sort_by_key(|(format, _)| format.extension());
+            copies
+        };
+        assert_eq!(copies("photo.jpg"), [(ImageFormat::Avif, Some(75))]);
+        assert_eq!(copies("chart.png"), [(ImageFormat::Avif, Some(75)), (ImageFormat::Webp, None)]);
+    }
+}
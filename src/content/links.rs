@@ -0,0 +1,252 @@
+use anyhow::{bail, Result};
+use comrak::nodes::{AstNode, NodeValue};
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+use super::page::Page;
+use super::processor::ContentProcessor;
+use super::summary::CONTINUE_READING_ID;
+use crate::config::{Config, LinkPolicy};
+
+/// A link to another page (or a fragment of the same page) found in markdown
+#[derive(Debug, Clone)]
+pub struct InternalLink {
+    /// Link destination as written by the author
+    pub href: String,
+    /// URL of the linked page, `None` when the source file doesn't exist. For links
+    /// written as URLs, the site-relative path they resolve to.
+    pub target: Option<String>,
+    pub fragment: Option<String>,
+    /// Whether the author wrote a URL rather than a markdown source path, so the target
+    /// may also be a static file or generated output
+    pub by_url: bool,
+}
+
+pub struct LinkResolver;
+
+impl LinkResolver {
+    /// Rewrite links to markdown sources into permalinks. `@/path.md` is relative to the
+    /// input directory, any other `.md` path to the linking file; `#id` links point into
+    /// the same page. Site-relative (`/posts/x.html`) and other relative URLs are left as
+    /// written. Returns every internal link so targets and anchors can be checked once the
+    /// whole site is built.
+    pub fn rewrite<'a>(root: &'a AstNode<'a>, source_path: &str, input_dir: &str) -> Vec<InternalLink> {
+        let mut links = Vec::new();
+
+        for node in root.descendants() {
+            let mut data = node.data.borrow_mut();
+            let NodeValue::Link(link) = &mut data.value else {
+                continue;
+            };
+
+            let href = link.url.clone();
+            // A bare `#` points at the top of the page and needs no anchor
+            let (path, fragment) = match href.split_once('#') {
+                Some((path, fragment)) => (path, Some(fragment.to_string()).filter(|f| !f.is_empty())),
+                None => (href.as_str(), None),
+            };
+
+            let target_source = if let Some(rooted) = path.strip_prefix("@/") {
+                Path::new(input_dir).join(rooted)
+            } else if path.is_empty() && fragment.is_some() {
+                PathBuf::from(source_path)
+            } else if path.is_empty() || path.starts_with('?') || path.starts_with("//") || Self::has_scheme(path) {
+                continue;
+            } else if path.ends_with(".md") && !path.starts_with('/') {
+                Path::new(source_path).parent().unwrap_or(Path::new("")).join(path)
+            } else {
+                let page_url = ContentProcessor::get_url(source_path, input_dir);
+                let target = Some(Self::resolve_url(path, &page_url));
+                links.push(InternalLink { href, target, fragment, by_url: true });
+                continue;
+            };
+
+            let target_source = Self::normalize(&target_source);
+            let target = target_source
+                .exists()
+                .then(|| ContentProcessor::get_url(&target_source.to_string_lossy(), input_dir));
+
+            // Same-page fragments keep their short form
+            if let Some(url) = target.as_ref().filter(|_| !path.is_empty()) {
+                link.url = match &fragment {
+                    Some(fragment) => format!("{}#{}", url, fragment),
+                    None => url.clone(),
+                };
+            }
+
+            links.push(InternalLink { href, target, fragment, by_url: false });
+        }
+
+        links
+    }
+
+    /// `mailto:`, `https:` and other URLs with a scheme
+    fn has_scheme(path: &str) -> bool {
+        path.split(['/', '?']).next().is_some_and(|first| first.contains(':'))
+    }
+
+    /// Site-relative path a URL written on the page at `page_url` points to, without its query
+    fn resolve_url(path: &str, page_url: &str) -> String {
+        let path = path.split('?').next().unwrap_or_default();
+        let absolute = if path.starts_with('/') {
+            path.to_string()
+        } else {
+            let directory = page_url.rsplit_once('/').map_or("", |(directory, _)| directory);
+            format!("{}/{}", directory, path)
+        };
+        let normalized = Self::normalize(Path::new(&absolute)).to_string_lossy().into_owned();
+        if absolute.ends_with('/') && normalized != "/" {
+            format!("{}/", normalized)
+        } else {
+            normalized
+        }
+    }
+
+    /// Resolve `.` and `..` without touching the filesystem, so paths map onto page URLs
+    pub(super) fn normalize(path: &Path) -> PathBuf {
+        let mut normalized = PathBuf::new();
+        for component in path.components() {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    normalized.pop();
+                }
+                other => normalized.push(other),
+            }
+        }
+        normalized
+    }
+}
+
+pub struct LinkValidator;
+
+impl LinkValidator {
+    /// Check that every internal link points at an existing page and, when it has one,
+    /// an existing heading or figure anchor. Links written as URLs may also point at any file
+    /// in the output directory, so this runs once the whole site is written. Problems fail
+    /// the build or are printed as warnings according to `[links] on_missing`.
+    pub fn validate(pages: &[Page], config: &Config, output_dir: &str) -> Result<()> {
+        let anchors: HashMap<&str, HashSet<&str>> = pages
+            .iter()
+            .map(|page| {
+                let mut ids: HashSet<&str> = page.headings.iter().map(|heading| heading.id.as_str()).collect();
+                ids.extend(page.figures.iter().filter_map(|figure| figure.id.as_deref()));
+                ids.insert(CONTINUE_READING_ID);
+                (page.url.as_str(), ids)
+            })
+            .collect();
+
+        let mut problems = Vec::new();
+        for page in pages {
+            for link in &page.links {
+                let Some(target) = &link.target else {
+                    problems.push(format!("{}: link to missing page '{}'", page.source_path, link.href));
+                    continue;
+                };
+                let target = if link.by_url {
+                    match Self::find_output(target, output_dir) {
+                        Some(file) => file,
+                        None => {
+                            problems.push(format!("{}: link to missing file '{}'", page.source_path, link.href));
+                            continue;
+                        }
+                    }
+                } else {
+                    target.clone()
+                };
+                let Some(fragment) = &link.fragment else {
+                    continue;
+                };
+                // Only pages have anchors to check
+                if link.by_url && !anchors.contains_key(target.as_str()) {
+                    continue;
+                }
+                if !anchors.get(target.as_str()).is_some_and(|ids| ids.contains(fragment.as_str())) {
+                    problems.push(format!("{}: link to missing anchor '{}'", page.source_path, link.href));
+                }
+            }
+        }
+
+        if problems.is_empty() {
+            return Ok(());
+        }
+
+        match config.links.on_missing {
+            LinkPolicy::Warn => {
+                for problem in &problems {
+                    eprintln!("⚠️  Broken link in {}", problem);
+                }
+                Ok(())
+            }
+            LinkPolicy::Error => {
+                for problem in &problems {
+                    eprintln!("❌ Broken link in {}", problem);
+                }
+                bail!("Found {} broken internal link(s)", problems.len())
+            }
+        }
+    }
+
+    /// Site-relative path of the output file a URL is served from, trying `index.html` for
+    /// directories
+    fn find_output(url: &str, output_dir: &str) -> Option<String> {
+        if url != "/" && !url.ends_with('/') && Path::new(&format!("{}{}", output_dir, url)).is_file() {
+            return Some(url.to_string());
+        }
+        let index = format!("{}/index.html", url.trim_end_matches('/'));
+        Path::new(&format!("{}{}", output_dir, index)).is_file().then_some(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use comrak::{parse_document, Arena, ComrakOptions};
+    use crate::utils::TestDir;
+
+    /// Rewrite the links of a page at `posts/page.md` in a throwaway input directory holding
+    /// `posts/other.md` too, returning the links and the rendered hrefs
+    fn rewrite(markdown: &str) -> (Vec<InternalLink>, Vec<String>) {
+        let dir = TestDir::new("links");
+        let source_path = dir.write("posts/page.md", markdown);
+        dir.write("posts/other.md", "# Other");
+        let input_dir = dir.path().to_string_lossy().into_owned();
+
+        let arena = Arena::new();
+        let root = parse_document(&arena, markdown, &ComrakOptions::default());
+        let links = LinkResolver::rewrite(root, &source_path, &input_dir);
+        let hrefs = root
+            .descendants()
+            .filter_map(|node| match &node.data.borrow().value {
+                NodeValue::Link(link) => Some(link.url.clone()),
+                _ => None,
+            })
+            .collect();
+        (links, hrefs)
+    }
+
+    #[test]
+    fn source_links_become_permalinks() {
+        let (links, hrefs) = rewrite("[a](other.md) [b](@/posts/other.md#intro) [c](#top) [d](missing.md)");
+        assert_eq!(hrefs, ["/posts/other.html", "/posts/other.html#intro", "#top", "missing.md"]);
+        let targets: Vec<_> = links.iter().map(|link| link.target.as_deref()).collect();
+        assert_eq!(targets, [Some("/posts/other.html"), Some("/posts/other.html"), Some("/posts/page.html"), None]);
+        assert!(links.iter().all(|link| !link.by_url));
+        assert_eq!(links[1].fragment.as_deref(), Some("intro"));
+    }
+
+    #[test]
+    fn url_links_are_recorded_with_their_site_path() {
+        let (links, hrefs) = rewrite("[a](/posts/old-name.html) [b](../about/?x=1) [c](image.png#top) [d](/)");
+        assert_eq!(hrefs, ["/posts/old-name.html", "../about/?x=1", "image.png#top", "/"]);
+        let targets: Vec<_> = links.iter().map(|link| link.target.as_deref().unwrap()).collect();
+        assert_eq!(targets, ["/posts/old-name.html", "/about/", "/posts/image.png", "/"]);
+        assert!(links.iter().all(|link| link.by_url));
+    }
+
+    #[test]
+    fn external_links_are_ignored() {
+        let (links, _) = rewrite("[a](https://example.com/x.md) [b](mailto:me@example.com) [c](//cdn.example.com/x) [d](?page=2)");
+        assert!(links.is_empty());
+    }
+}
@@ -1,12 +1,135 @@
-use comrak::{markdown_to_html, ComrakOptions};
-use std::path::Path;
+use comrak::nodes::{AstNode, NodeValue};
+use comrak::{format_html_with_plugins, parse_document, Arena, ComrakOptions, ComrakPlugins};
+
+use super::callout::CalloutRenderer;
+use super::figure::{Figure, FigureRenderer};
+use super::heading::{Heading, HeadingCollector, HeadingRenderer};
+use super::images::{ImageVariant, ResponsiveImages};
+use super::links::{InternalLink, LinkResolver};
+use super::post_list::{POST_LIST_MARKER, POST_LIST_PLACEHOLDER};
+use super::processor::ContentProcessor;
+use super::reading::ReadingStats;
+use super::summary::{Summary, SummaryExtractor};
+use super::toc::{TableOfContents, TocEntry, TOC_MARKER};
+use crate::config::Config;
+
+/// Result of rendering a markdown document
+pub struct RenderedMarkdown {
+    pub html: String,
+    pub headings: Vec<Heading>,
+    pub toc: Vec<TocEntry>,
+    /// Whether the document placed its table of contents with a `[[toc]]` marker
+    pub has_toc_marker: bool,
+    pub summary: Summary,
+    pub stats: ReadingStats,
+    /// Plain text of the document, without code blocks
+    pub text: String,
+    /// Links to other pages and anchors, already rewritten to permalinks
+    pub links: Vec<InternalLink>,
+    /// Captioned images, in document order
+    pub figures: Vec<Figure>,
+    /// Resized image variants the HTML refers to
+    pub images: Vec<ImageVariant>,
+    /// Problems that don't stop the page from rendering, such as repeated heading IDs
+    pub warnings: Vec<String>,
+}
 
 pub struct MarkdownProcessor;
 
 impl MarkdownProcessor {
-    /// Convert markdown content to HTML
-    pub fn to_html(markdown: &str) -> String {
-        markdown_to_html(markdown, &ComrakOptions::default())
+    /// Render markdown to HTML, collecting its headings and table of contents.
+    /// `[[toc]]` markers are replaced by the table of contents when `inline_toc` is set
+    /// and removed otherwise. Links to other markdown files and local images are resolved
+    /// against `source_path` and `input_dir`.
+    pub fn render(markdown: &str, source_path: &str, input_dir: &str, config: &Config, inline_toc: bool) -> RenderedMarkdown {
+        let arena = Arena::new();
+        let options = Self::options();
+        let root = parse_document(&arena, markdown, &options);
+
+        CalloutRenderer::transform(&arena, root);
+        let links = LinkResolver::rewrite(root, source_path, input_dir);
+        let figures = FigureRenderer::transform(&arena, root, source_path, config);
+        let images = ResponsiveImages::transform(&arena, root, source_path, input_dir, config);
+
+        let (headings, warnings) = HeadingCollector::collect(root);
+        let toc = TableOfContents::build(&headings, config.toc.min_depth, config.toc.max_depth);
+        let toc_html = if inline_toc { TableOfContents::to_html(&toc) } else { String::new() };
+        let has_toc_marker = Self::replace_markers(&arena, root, TOC_MARKER, &toc_html);
+        // The post list needs every page, so the site generator fills it in
+        Self::replace_markers(&arena, root, POST_LIST_MARKER, POST_LIST_PLACEHOLDER);
+        let summary = SummaryExtractor::extract(&arena, root, config.summary.words);
+        // Code blocks carry no text nodes, so they are excluded from the count
+        let text = Self::text_content(root);
+        let stats = ReadingStats::from_text(&text, config.reading.words_per_minute);
+
+        let heading_renderer = HeadingRenderer::new(&headings, config.anchors.permalinks);
+        let mut plugins = ComrakPlugins::default();
+        plugins.render.heading_adapter = Some(&heading_renderer);
+
+        let mut html = Vec::new();
+        format_html_with_plugins(root, &options, &mut html, &plugins)
+            .expect("writing HTML to memory cannot fail");
+
+        RenderedMarkdown {
+            html: String::from_utf8_lossy(&html).into_owned(),
+            headings,
+            toc,
+            has_toc_marker,
+            summary,
+            stats,
+            text,
+            links,
+            figures,
+            images,
+            warnings,
+        }
+    }
+
+    /// Markdown extensions enabled for all content
+    fn options() -> ComrakOptions<'static> {
+        let mut options = ComrakOptions::default();
+        options.extension.alerts = true;
+        options.extension.strikethrough = true;
+        options.extension.table = true;
+        options.extension.tasklist = true;
+        options.extension.autolink = true;
+        options
+    }
+
+    /// Replace paragraphs consisting only of `marker`, such as `[[toc]]`, with the given HTML
+    fn replace_markers<'a>(arena: &'a Arena<AstNode<'a>>, root: &'a AstNode<'a>, marker: &str, html: &str) -> bool {
+        let paragraphs: Vec<_> = root
+            .descendants()
+            .filter(|node| matches!(node.data.borrow().value, NodeValue::Paragraph))
+            .filter(|node| Self::text_content(node).trim().eq_ignore_ascii_case(marker))
+            .collect();
+
+        for paragraph in &paragraphs {
+            paragraph.insert_before(arena.alloc(NodeValue::Raw(html.to_string()).into()));
+            paragraph.detach();
+        }
+
+        !paragraphs.is_empty()
+    }
+
+    /// Concatenate the plain text of a node and its descendants
+    pub fn text_content<'a>(node: &'a AstNode<'a>) -> String {
+        let mut text = String::new();
+        for descendant in node.descendants() {
+            match &descendant.data.borrow().value {
+                NodeValue::Text(literal) => text.push_str(literal),
+                NodeValue::Code(code) => text.push_str(&code.literal),
+                NodeValue::SoftBreak | NodeValue::LineBreak => text.push(' '),
+                // Separate the text of adjacent blocks
+                NodeValue::Paragraph | NodeValue::Heading(_) | NodeValue::Item(_) | NodeValue::TableCell
+                    if !text.is_empty() && !text.ends_with(' ') =>
+                {
+                    text.push(' ')
+                }
+                _ => {}
+            }
+        }
+        text
     }
 
     /// Extract title from markdown content (first H1 header)
@@ -15,19 +138,23 @@ impl MarkdownProcessor {
         for line in markdown_content.lines() {
             let trimmed = line.trim();
             if trimmed.starts_with("# ") {
-                return trimmed.trim_start_matches("# ").trim().to_string();
+                let title = trimmed.trim_start_matches("# ").trim();
+                // Drop a custom heading ID (`# Title {#id}`)
+                let title = match title.rfind(" {#") {
+                    Some(start) if title.ends_with('}') => title[..start].trim_end(),
+                    _ => title,
+                };
+                return title.to_string();
             }
         }
-        
+
         // Fallback to extracting from file path
         Self::extract_title_from_path(fallback_path)
     }
 
-    /// Extract title from file path
+    /// Extract title from file path, or from the directory name of a page bundle
     pub fn extract_title_from_path(path: &str) -> String {
-        Path::new(path)
-            .file_stem()
-            .and_then(|s| s.to_str())
+        ContentProcessor::page_stem(path)
             .map(|s| {
                 // Remove date prefix if present (YYYY-MM-DD-)
                 if s.len() > 11 && s.chars().nth(4) == Some('-') && s.chars().nth(7) == Some('-') {
@@ -49,4 +176,4 @@ impl MarkdownProcessor {
             .collect::<Vec<String>>()
             .join(" ")
     }
-}
\ No newline at end of file
+}
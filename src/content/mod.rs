@@ -1,5 +1,22 @@
+pub mod callout;
+pub mod figure;
+pub mod front_matter;
+pub mod heading;
+pub mod images;
+pub mod links;
 pub mod markdown;
+pub mod page;
+pub mod podcast;
+pub mod post_list;
 pub mod processor;
+pub mod reading;
+pub mod section;
+pub mod summary;
+pub mod toc;
 
 pub use markdown::MarkdownProcessor;
-pub use processor::ContentProcessor;
\ No newline at end of file
+pub use page::Page;
+pub use post_list::PostList;
+pub use processor::ContentProcessor;
+pub use section::SectionDefaults;
+pub use toc::TableOfContents;
@@ -0,0 +1,219 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime};
+use std::fs;
+
+use super::front_matter::{FrontMatter, PageDefaults};
+use super::figure::Figure;
+use super::heading::Heading;
+use super::images::ImageVariant;
+use super::links::InternalLink;
+use super::markdown::MarkdownProcessor;
+use super::podcast::PodcastEpisode;
+use super::processor::ContentProcessor;
+use super::summary::CONTINUE_READING_ID;
+use super::toc::TocEntry;
+use crate::config::Config;
+use crate::theme::{Layout, Theme};
+use crate::utils::HtmlUtils;
+
+/// A markdown source file rendered and ready for templating
+#[derive(Default)]
+pub struct Page {
+    pub source_path: String,
+    /// Site-relative URL of the generated page
+    pub url: String,
+    /// Top-level content directory the page belongs to (`""` for the root)
+    pub section: String,
+    pub title: String,
+    /// Publication date from front matter or a `YYYY-MM-DD-` file name prefix
+    pub date: Option<DateTime<FixedOffset>>,
+    /// Last modification date from front matter `updated`
+    pub updated: Option<DateTime<FixedOffset>>,
+    pub tags: Vec<String>,
+    /// Site-relative path or URL of the page's share image
+    pub image: Option<String>,
+    /// Site-relative path of the generated social card, set by the site generator
+    pub social_card: Option<String>,
+    /// Plain-text summary for listings, feeds and `<meta name="description">`
+    pub summary: String,
+    /// Where a "Read more" link after the summary should point
+    pub read_more_url: String,
+    pub word_count: usize,
+    /// Estimated reading time in minutes
+    pub reading_time: usize,
+    /// Rendered HTML body
+    pub content: String,
+    /// Plain-text body, used for the search index
+    pub text: String,
+    /// Every heading in the document with its anchor id
+    pub headings: Vec<Heading>,
+    /// Links to other pages, checked once the whole site is loaded
+    pub links: Vec<InternalLink>,
+    /// Captioned figures, which can be linked to by label like headings
+    pub figures: Vec<Figure>,
+    /// Resized image variants the content refers to, encoded by the site generator
+    pub images: Vec<ImageVariant>,
+    /// Nested table of contents for the page headings
+    pub toc: Vec<TocEntry>,
+    /// Whether the template should place the table of contents above the content
+    pub show_toc: bool,
+    /// Audio enclosure, set for pages in the configured podcast section
+    pub episode: Option<PodcastEpisode>,
+    /// Whether the page is listed in `sitemap.xml`
+    pub in_sitemap: bool,
+    /// Whether the page is included in the search index
+    pub in_search: bool,
+    /// Template the page is rendered with
+    pub layout: Layout,
+    /// Theme overriding the site theme, from front matter or the section
+    pub theme: Option<Theme>,
+    /// Problems found while rendering, printed by the site generator
+    pub warnings: Vec<String>,
+}
+
+impl Page {
+    /// Read and render a markdown file. `defaults` are the section's `_index.md` values for
+    /// keys the page doesn't set itself.
+    pub fn load(source_path: &str, input_dir: &str, defaults: &PageDefaults, config: &Config) -> Result<Page> {
+        let source = fs::read_to_string(source_path)?;
+        let (front_matter, markdown) = FrontMatter::parse(&source)
+            .with_context(|| format!("Invalid front matter in {}", source_path))?;
+
+        // An explicit `toc: false` also suppresses inline `[[toc]]` markers
+        let toc_enabled = front_matter.toc.unwrap_or(config.toc.enabled);
+        let rendered = MarkdownProcessor::render(markdown, source_path, input_dir, config, front_matter.toc != Some(false));
+
+        let title = front_matter
+            .title
+            .clone()
+            .unwrap_or_else(|| MarkdownProcessor::extract_title(markdown, source_path));
+
+        let date = match &front_matter.date {
+            Some(date) => Some(Self::parse_date(date)
+                .with_context(|| format!("Invalid date '{}' in {}", date, source_path))?),
+            None => Self::date_from_path(source_path),
+        };
+        let updated = match &front_matter.updated {
+            Some(updated) => Some(Self::parse_date(updated)
+                .with_context(|| format!("Invalid updated date '{}' in {}", updated, source_path))?),
+            None => None,
+        };
+
+        let section = ContentProcessor::get_section(source_path, input_dir);
+        let episode = if config.podcast.section.as_ref() == Some(&section) {
+            PodcastEpisode::from_front_matter(&front_matter, source_path, input_dir)?
+        } else {
+            None
+        };
+
+        let url = ContentProcessor::get_url(source_path, input_dir);
+        let read_more_url = if rendered.summary.has_more_marker {
+            format!("{}#{}", url, CONTINUE_READING_ID)
+        } else {
+            url.clone()
+        };
+        let image = front_matter.image.as_deref().map(|image| Self::resolve_link(image, &url));
+
+        let layout = match front_matter.layout.as_ref().or(defaults.layout.as_ref()) {
+            Some(layout) => layout.parse::<Layout>().map_err(anyhow::Error::msg)
+                .with_context(|| format!("Invalid layout in {}", source_path))?,
+            None => Layout::default(),
+        };
+        let theme = match front_matter.theme.as_ref().or(defaults.theme.as_ref()) {
+            Some(theme) => Some(theme.parse::<Theme>().map_err(anyhow::Error::msg)
+                .with_context(|| format!("Invalid theme in {}", source_path))?),
+            None => None,
+        };
+
+        Ok(Page {
+            source_path: source_path.to_string(),
+            url,
+            section,
+            title,
+            date,
+            updated,
+            tags: front_matter.tags,
+            image,
+            social_card: None,
+            summary: front_matter.description.unwrap_or(rendered.summary.text),
+            read_more_url,
+            word_count: rendered.stats.word_count,
+            reading_time: rendered.stats.reading_time,
+            show_toc: toc_enabled && !rendered.has_toc_marker && !rendered.toc.is_empty(),
+            content: rendered.html,
+            text: rendered.text,
+            headings: rendered.headings,
+            links: rendered.links,
+            figures: rendered.figures,
+            images: rendered.images,
+            warnings: rendered.warnings,
+            toc: rendered.toc,
+            episode,
+            in_sitemap: front_matter.sitemap != Some(false),
+            in_search: front_matter.search != Some(false),
+            layout,
+            theme,
+        })
+    }
+
+    /// Summary paragraph followed by a "Read more" link, for listings and feeds.
+    /// `base_url` is prepended to the link, pass `""` for a site-relative one.
+    pub fn summary_html(&self, base_url: &str) -> String {
+        format!(
+            "<p class=\"summary\">{}</p>\n<p class=\"read-more\"><a href=\"{}{}\">Read more</a></p>\n",
+            HtmlUtils::escape(&self.summary),
+            HtmlUtils::escape(base_url.trim_end_matches('/')),
+            HtmlUtils::escape(&self.read_more_url)
+        )
+    }
+
+    /// Resolve a link relative to the page's directory, leaving site-relative paths and URLs as they are
+    fn resolve_link(link: &str, page_url: &str) -> String {
+        if link.starts_with('/') || link.contains("://") {
+            return link.to_string();
+        }
+        let directory = page_url.rsplit_once('/').map_or("", |(directory, _)| directory);
+        format!("{}/{}", directory, link.trim_start_matches("./"))
+    }
+
+    /// Parse a front matter date, either a plain date or a full RFC 3339 timestamp
+    fn parse_date(date: &str) -> Result<DateTime<FixedOffset>> {
+        if let Ok(datetime) = DateTime::parse_from_rfc3339(date) {
+            return Ok(datetime);
+        }
+        let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")?;
+        Ok(date.and_time(NaiveTime::MIN).and_utc().fixed_offset())
+    }
+
+    /// Extract the date from a `YYYY-MM-DD-title.md` file name or `YYYY-MM-DD-title/index.md` bundle
+    fn date_from_path(source_path: &str) -> Option<DateTime<FixedOffset>> {
+        let filename = ContentProcessor::page_stem(source_path)?;
+        let date = NaiveDate::parse_from_str(filename.get(..10)?, "%Y-%m-%d").ok()?;
+        Some(date.and_time(NaiveTime::MIN).and_utc().fixed_offset())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_date_accepts_plain_dates_and_timestamps() {
+        assert_eq!(Page::parse_date("2025-01-02").unwrap().to_rfc3339(), "2025-01-02T00:00:00+00:00");
+        assert_eq!(
+            Page::parse_date("2025-01-02T08:30:00+02:00").unwrap().to_rfc3339(),
+            "2025-01-02T08:30:00+02:00"
+        );
+        assert!(Page::parse_date("January 2nd").is_err());
+        assert!(Page::parse_date("2025-13-01").is_err());
+    }
+
+    #[test]
+    fn date_from_path_reads_file_and_bundle_prefixes() {
+        let date = |path| Page::date_from_path(path).map(|date| date.date_naive().to_string());
+        assert_eq!(date("content/posts/2025-01-02-hello.md").as_deref(), Some("2025-01-02"));
+        assert_eq!(date("content/posts/2025-01-02-hello/index.md").as_deref(), Some("2025-01-02"));
+        assert_eq!(date("content/posts/hello.md"), None);
+        assert_eq!(date("content/2025.md"), None);
+    }
+}
@@ -0,0 +1,76 @@
+use anyhow::{bail, Result};
+use std::fs;
+use std::path::Path;
+
+use super::front_matter::FrontMatter;
+
+/// Audio enclosure and episode metadata for a page in the podcast section
+#[derive(Debug, Clone)]
+pub struct PodcastEpisode {
+    /// Site-relative path or absolute URL of the audio file
+    pub audio_url: String,
+    /// File size in bytes
+    pub length: u64,
+    pub mime_type: String,
+    pub duration: Option<String>,
+    pub episode: Option<u32>,
+    pub season: Option<u32>,
+}
+
+impl PodcastEpisode {
+    /// Build episode metadata from front matter. Local `audio` paths are resolved
+    /// relative to the markdown file, or to the input directory when they start with `/`.
+    pub fn from_front_matter(front_matter: &FrontMatter, source_path: &str, input_dir: &str) -> Result<Option<Self>> {
+        let Some(audio) = &front_matter.audio else {
+            return Ok(None);
+        };
+
+        let (audio_url, length) = if audio.starts_with("http://") || audio.starts_with("https://") {
+            eprintln!("⚠️  Remote audio in {} has unknown size, enclosure length set to 0", source_path);
+            (audio.clone(), 0)
+        } else {
+            let local_path = match audio.strip_prefix('/') {
+                Some(rooted) => Path::new(input_dir).join(rooted),
+                None => Path::new(source_path).parent().unwrap_or(Path::new("")).join(audio),
+            };
+            let Ok(metadata) = fs::metadata(&local_path) else {
+                bail!("Audio file not found: {} (referenced from {})", local_path.display(), source_path);
+            };
+
+            let local_path = local_path.to_string_lossy().to_string();
+            let relative = local_path.strip_prefix(input_dir).unwrap_or(&local_path).trim_start_matches('/');
+            let audio_url = format!("/{}", relative);
+            (audio_url, metadata.len())
+        };
+
+        Ok(Some(PodcastEpisode {
+            mime_type: Self::mime_type(&audio_url).to_string(),
+            audio_url,
+            length,
+            duration: front_matter.duration.clone(),
+            episode: front_matter.episode,
+            season: front_matter.season,
+        }))
+    }
+
+    /// MIME type of an audio file based on its extension
+    fn mime_type(path: &str) -> &'static str {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+
+        match extension.as_str() {
+            "mp3" => "audio/mpeg",
+            "m4a" => "audio/x-m4a",
+            "aac" => "audio/aac",
+            "ogg" | "oga" => "audio/ogg",
+            "opus" => "audio/opus",
+            "wav" => "audio/wav",
+            "flac" => "audio/flac",
+            "mp4" => "video/mp4",
+            _ => "application/octet-stream",
+        }
+    }
+}
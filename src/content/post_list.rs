@@ -0,0 +1,77 @@
+use super::Page;
+use crate::utils::HtmlUtils;
+
+/// Marker authors can place on its own line to list the site's posts
+pub const POST_LIST_MARKER: &str = "[[posts]]";
+
+/// Left in the rendered HTML in place of the marker until every page is loaded
+pub const POST_LIST_PLACEHOLDER: &str = "<!-- dodge:posts -->";
+
+pub struct PostList;
+
+impl PostList {
+    /// Dated pages, newest first, each with its summary and a "Read more" link
+    pub fn to_html(pages: &[Page]) -> String {
+        let mut posts: Vec<&Page> = pages.iter().filter(|page| page.date.is_some()).collect();
+        posts.sort_by_key(|page| std::cmp::Reverse(page.date));
+
+        let mut html = String::from("<ul class=\"post-list\">\n");
+        for post in posts {
+            let date = post.date.expect("only dated pages are listed");
+            html.push_str(&format!(
+                "<li>\n<h2><a href=\"{}\">{}</a></h2>\n<time datetime=\"{}\">{}</time>\n{}</li>\n",
+                HtmlUtils::escape(&post.url),
+                HtmlUtils::escape(&post.title),
+                date.format("%Y-%m-%d"),
+                date.format("%B %-d, %Y"),
+                post.summary_html("")
+            ));
+        }
+        html.push_str("</ul>\n");
+        html
+    }
+
+    /// Replace the post list placeholder in every page that has one
+    pub fn expand(pages: &mut [Page]) {
+        let list = Self::to_html(pages);
+        for page in pages.iter_mut().filter(|page| page.content.contains(POST_LIST_PLACEHOLDER)) {
+            page.content = page.content.replace(POST_LIST_PLACEHOLDER, &list);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::content::front_matter::PageDefaults;
+    use crate::utils::TestDir;
+
+    #[test]
+    fn lists_posts_newest_first_with_summaries() {
+        let dir = TestDir::new("post-list");
+        let sources = [
+            dir.write("index.md", "# Home\n\n[[posts]]"),
+            dir.write("posts/2025-01-01-old.md", "# Old\n\nSome ~~struck~~ **bold** words that run past the limit."),
+            dir.write("posts/2025-02-01-new.md", "# New\n\nJust the intro.\n\n<!-- more -->\n\nThe rest."),
+        ];
+        let mut config = Config::default();
+        config.summary.words = 4;
+
+        let input_dir = dir.path().to_string_lossy();
+        let mut pages: Vec<Page> = sources
+            .iter()
+            .map(|source| Page::load(source, &input_dir, &PageDefaults::default(), &config).unwrap())
+            .collect();
+        PostList::expand(&mut pages);
+
+        let list = &pages[0].content;
+        assert!(!list.contains(POST_LIST_PLACEHOLDER) && !list.contains(POST_LIST_MARKER));
+        let (new, old) = (list.find("/posts/2025-02-01-new.html").unwrap(), list.find("/posts/2025-01-01-old.html").unwrap());
+        assert!(new < old);
+        assert!(list.contains(r#"<p class="summary">Just the intro.</p>"#));
+        assert!(list.contains(r##"<a href="/posts/2025-02-01-new.html#continue-reading">Read more</a>"##));
+        assert!(list.contains(r#"<p class="summary">Some struck bold words…</p>"#));
+        assert!(list.contains(r#"<time datetime="2025-01-01">January 1, 2025</time>"#));
+    }
+}
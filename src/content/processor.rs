@@ -3,16 +3,20 @@ use std::fs;
 use std::path::Path;
 use glob::glob;
 
+use super::section::SECTION_INDEX;
+
 pub struct ContentProcessor;
 
 impl ContentProcessor {
-    /// Collect all markdown files matching the pattern
+    /// Collect all markdown files matching the pattern. Section `_index.md` files only hold
+    /// defaults and aren't pages.
     pub fn collect_posts(input_dir: &str) -> Result<Vec<String>> {
         let pattern = format!("{}/**/*.md", input_dir);
         let mut posts = Vec::new();
 
         for entry in glob(&pattern)? {
             match entry {
+                Ok(path) if path.file_name().is_some_and(|name| name == SECTION_INDEX) => {}
                 Ok(path) => posts.push(path.to_string_lossy().to_string()),
                 Err(e) => eprintln!("⚠️  Error reading path: {}", e),
             }
@@ -22,6 +26,67 @@ impl ContentProcessor {
         Ok(posts)
     }
 
+    /// Collect every file under a directory
+    pub fn collect_files(dir: &str) -> Result<Vec<String>> {
+        let pattern = format!("{}/**/*", glob::Pattern::escape(dir));
+        let options = glob::MatchOptions { require_literal_leading_dot: false, ..Default::default() };
+        let mut files = Vec::new();
+
+        for entry in glob::glob_with(&pattern, options)? {
+            match entry {
+                Ok(path) if path.is_file() => files.push(path.to_string_lossy().to_string()),
+                Ok(_) => {}
+                Err(e) => eprintln!("⚠️  Error reading path: {}", e),
+            }
+        }
+
+        files.sort();
+        Ok(files)
+    }
+
+    /// Collect every non-markdown file under a directory, skipping hidden files
+    pub fn collect_assets(dir: &str) -> Result<Vec<String>> {
+        let assets = Self::collect_files(dir)?
+            .into_iter()
+            .filter(|file| {
+                let path = Path::new(file);
+                let hidden = path
+                    .strip_prefix(dir)
+                    .unwrap_or(path)
+                    .components()
+                    .any(|part| part.as_os_str().to_string_lossy().starts_with('.'));
+                let markdown = path.extension().is_some_and(|ext| ext == "md");
+                !hidden && !markdown
+            })
+            .collect();
+        Ok(assets)
+    }
+
+    /// Name a page is known by: the file stem, or the directory name for a
+    /// page bundle (`posts/my-post/index.md`)
+    pub fn page_stem(input_path: &str) -> Option<&str> {
+        let path = Path::new(input_path);
+        let stem = path.file_stem()?.to_str()?;
+        if stem != "index" {
+            return Some(stem);
+        }
+        path.parent()?.file_name()?.to_str().or(Some(stem))
+    }
+
+    /// Get the section of a markdown file: its top-level directory under the input
+    /// directory, or `""` for files at the root
+    pub fn get_section(input_path: &str, input_dir: &str) -> String {
+        let relative = input_path
+            .strip_prefix(input_dir)
+            .unwrap_or(input_path)
+            .trim_start_matches('/');
+
+        match relative.split_once('/') {
+            Some((section, _)) => section.to_string(),
+            None => String::new(),
+        }
+    }
+
     /// Get output path for a markdown file
     pub fn get_output_path(input_path: &str, input_dir: &str, output_dir: &str) -> String {
         input_path
@@ -29,6 +94,14 @@ impl ContentProcessor {
             .replace(".md", ".html")
     }
 
+    /// Get the site-relative URL of the page generated from a markdown file
+    pub fn get_url(input_path: &str, input_dir: &str) -> String {
+        format!("/{}", input_path
+            .replace(input_dir, "")
+            .trim_start_matches('/')
+            .replace(".md", ".html"))
+    }
+
     /// Ensure output directory exists for a file
     pub fn ensure_output_dir(output_path: &str) -> Result<()> {
         if let Some(parent) = Path::new(output_path).parent() {
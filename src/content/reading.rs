@@ -0,0 +1,82 @@
+/// Word count and estimated reading time of a document
+#[derive(Debug, Clone, Copy)]
+pub struct ReadingStats {
+    pub word_count: usize,
+    /// Estimated reading time in minutes (at least 1)
+    pub reading_time: usize,
+}
+
+impl ReadingStats {
+    /// Count words in plain text. Words are separated by whitespace only, so apostrophes and
+    /// hyphens inside a word ("don't", "well-known") don't split it, and tokens without any
+    /// letters or digits (a lone dash) aren't words. CJK characters count as one word each
+    /// since those scripts don't separate words with whitespace.
+    pub fn from_text(text: &str, words_per_minute: usize) -> ReadingStats {
+        let word_count: usize = text.split_whitespace().map(Self::count_token).sum();
+        let reading_time = word_count.div_ceil(words_per_minute.max(1)).max(1);
+        ReadingStats { word_count, reading_time }
+    }
+
+    /// Words in a whitespace-free token: each CJK character, plus each run of other text
+    /// between them that contains a letter or digit
+    fn count_token(token: &str) -> usize {
+        let mut count = 0;
+        let mut in_word = false;
+        for ch in token.chars() {
+            if Self::is_cjk(ch) {
+                count += 1;
+                in_word = false;
+            } else if ch.is_alphanumeric() && !in_word {
+                count += 1;
+                in_word = true;
+            }
+        }
+        count
+    }
+
+    fn is_cjk(ch: char) -> bool {
+        matches!(ch,
+            '\u{3040}'..='\u{30FF}'     // Hiragana, Katakana
+            | '\u{3400}'..='\u{4DBF}'   // CJK Extension A
+            | '\u{4E00}'..='\u{9FFF}'   // CJK Unified Ideographs
+            | '\u{AC00}'..='\u{D7AF}'   // Hangul Syllables
+            | '\u{F900}'..='\u{FAFF}'   // CJK Compatibility Ideographs
+            | '\u{20000}'..='\u{2FA1F}' // CJK Extensions B-F, Compatibility Supplement
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(text: &str) -> usize {
+        ReadingStats::from_text(text, 200).word_count
+    }
+
+    #[test]
+    fn apostrophes_and_hyphens_stay_inside_words() {
+        assert_eq!(words("don't stop"), 2);
+        assert_eq!(words("it\u{2019}s a well-known fact"), 4);
+        assert_eq!(words("state-of-the-art"), 1);
+    }
+
+    #[test]
+    fn punctuation_only_tokens_are_not_words() {
+        assert_eq!(words("one - two \u{2014} three ..."), 3);
+        assert_eq!(words("  "), 0);
+    }
+
+    #[test]
+    fn cjk_characters_count_individually() {
+        assert_eq!(words("日本語"), 3);
+        assert_eq!(words("Rust言語 rocks"), 4);
+    }
+
+    #[test]
+    fn reading_time_rounds_up_to_at_least_a_minute() {
+        assert_eq!(ReadingStats::from_text("", 200).reading_time, 1);
+        assert_eq!(ReadingStats::from_text(&"word ".repeat(201), 200).reading_time, 2);
+        assert_eq!(ReadingStats::from_text("a b c", 0).reading_time, 3);
+    }
+}
@@ -0,0 +1,91 @@
+use anyhow::{Context, Result};
+use glob::glob;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::front_matter::{FrontMatter, PageDefaults};
+
+/// File whose front matter `cascade` block sets defaults for a directory's pages
+pub const SECTION_INDEX: &str = "_index.md";
+
+/// The `cascade` blocks of every `_index.md` under the input directory, by directory
+pub struct SectionDefaults {
+    sections: HashMap<PathBuf, PageDefaults>,
+}
+
+impl SectionDefaults {
+    pub fn load(input_dir: &str) -> Result<Self> {
+        let mut sections = HashMap::new();
+        for entry in glob(&format!("{}/**/{}", input_dir, SECTION_INDEX))? {
+            let path = entry?;
+            let source = fs::read_to_string(&path)?;
+            let (front_matter, _) = FrontMatter::parse(&source)
+                .with_context(|| format!("Invalid front matter in {}", path.display()))?;
+            if let Some(directory) = path.parent() {
+                sections.insert(directory.to_path_buf(), front_matter.cascade);
+            }
+        }
+        Ok(Self { sections })
+    }
+
+    /// Defaults for a page: each key comes from the nearest `_index.md` setting it, walking
+    /// up from the page's directory to the input directory
+    pub fn for_page(&self, source_path: &str, input_dir: &str) -> PageDefaults {
+        let root = Path::new(input_dir);
+        let mut defaults = PageDefaults::default();
+        let mut directory = Path::new(source_path).parent();
+        while let Some(current) = directory {
+            if let Some(section) = self.sections.get(current) {
+                defaults = defaults.or(section);
+            }
+            if current == root || !current.starts_with(root) {
+                break;
+            }
+            directory = current.parent();
+        }
+        defaults
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::content::Page;
+    use crate::theme::{Layout, Theme};
+    use crate::utils::TestDir;
+
+    #[test]
+    fn nearest_section_sets_each_key() {
+        let dir = TestDir::new("sections");
+        dir.write(SECTION_INDEX, "---\ncascade:\n  layout: page\n---\n");
+        dir.write(&format!("ctf/{}", SECTION_INDEX), "---\ncascade:\n  theme: hacker\n---\n");
+        dir.write(&format!("ctf/web/{}", SECTION_INDEX), "---\ncascade:\n  layout: landing\n---\n");
+        let input_dir = dir.path().to_string_lossy();
+        let sections = SectionDefaults::load(&input_dir).unwrap();
+
+        let web = sections.for_page(&dir.join("ctf/web/xss.md"), &input_dir);
+        assert_eq!((web.layout.as_deref(), web.theme.as_deref()), (Some("landing"), Some("hacker")));
+        let ctf = sections.for_page(&dir.join("ctf/pwn.md"), &input_dir);
+        assert_eq!((ctf.layout.as_deref(), ctf.theme.as_deref()), (Some("page"), Some("hacker")));
+        let root = sections.for_page(&dir.join("about.md"), &input_dir);
+        assert_eq!((root.layout.as_deref(), root.theme.as_deref()), (Some("page"), None));
+    }
+
+    #[test]
+    fn page_front_matter_wins_over_sections() {
+        let dir = TestDir::new("sections-page");
+        dir.write(&format!("ctf/{}", SECTION_INDEX), "---\ncascade:\n  layout: landing\n  theme: hacker\n---\n");
+        let own = dir.write("ctf/own.md", "---\ntitle: Own\ntheme: vercel\n---\nBody");
+        let inherited = dir.write("ctf/inherited.md", "# Inherited");
+        let input_dir = dir.path().to_string_lossy();
+        let sections = SectionDefaults::load(&input_dir).unwrap();
+
+        let load = |source: &str| Page::load(source, &input_dir, &sections.for_page(source, &input_dir), &Config::default()).unwrap();
+        let own = load(&own);
+        assert_eq!((own.layout, own.theme), (Layout::Landing, Some(Theme::Vercel)));
+        let inherited = load(&inherited);
+        assert_eq!((inherited.layout, inherited.theme), (Layout::Landing, Some(Theme::Hacker)));
+    }
+}
@@ -0,0 +1,75 @@
+use comrak::nodes::{AstNode, NodeValue};
+use comrak::Arena;
+
+use super::MarkdownProcessor;
+
+/// Anchor left in place of the `<!-- more -->` marker so "Read more" links can skip the excerpt
+pub const CONTINUE_READING_ID: &str = "continue-reading";
+
+/// Plain-text summary of a document
+pub struct Summary {
+    pub text: String,
+    /// Whether the summary was cut at an explicit `<!-- more -->` marker
+    pub has_more_marker: bool,
+}
+
+pub struct SummaryExtractor;
+
+impl SummaryExtractor {
+    /// Extract the text before a `<!-- more -->` marker, or the first `max_words` words.
+    /// Headings and code blocks are skipped. The marker is replaced by a
+    /// `continue-reading` anchor.
+    pub fn extract<'a>(arena: &'a Arena<AstNode<'a>>, root: &'a AstNode<'a>, max_words: usize) -> Summary {
+        let marker = root.children().find(|node| Self::is_more_marker(node));
+
+        let mut text = String::new();
+        for node in root.children() {
+            if marker.is_some_and(|marker| marker.same_node(node)) {
+                break;
+            }
+            if Self::is_prose(node) {
+                text.push_str(&MarkdownProcessor::text_content(node));
+                text.push(' ');
+            }
+        }
+
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let text = match marker {
+            Some(marker) => {
+                let anchor = format!("<span id=\"{}\"></span>\n", CONTINUE_READING_ID);
+                marker.insert_before(arena.alloc(NodeValue::Raw(anchor).into()));
+                marker.detach();
+                words.join(" ")
+            }
+            None if words.len() > max_words => format!("{}…", words[..max_words].join(" ")),
+            None => words.join(" "),
+        };
+
+        Summary {
+            text,
+            has_more_marker: marker.is_some(),
+        }
+    }
+
+    fn is_more_marker<'a>(node: &'a AstNode<'a>) -> bool {
+        match &node.data.borrow().value {
+            NodeValue::HtmlBlock(block) => {
+                let normalized: String = block.literal.split_whitespace().collect();
+                normalized.eq_ignore_ascii_case("<!--more-->")
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether a top-level node contributes to the summary text
+    fn is_prose<'a>(node: &'a AstNode<'a>) -> bool {
+        !matches!(
+            node.data.borrow().value,
+            NodeValue::Heading(_)
+                | NodeValue::CodeBlock(_)
+                | NodeValue::HtmlBlock(_)
+                | NodeValue::ThematicBreak
+                | NodeValue::Raw(_)
+        )
+    }
+}
@@ -0,0 +1,79 @@
+use super::heading::Heading;
+use crate::utils::HtmlUtils;
+
+/// Marker authors can place on its own line to position the table of contents
+pub const TOC_MARKER: &str = "[[toc]]";
+
+/// A node in the nested table of contents
+#[derive(Debug, Clone)]
+pub struct TocEntry {
+    pub level: u8,
+    pub id: String,
+    pub title: String,
+    pub children: Vec<TocEntry>,
+}
+
+pub struct TableOfContents;
+
+impl TableOfContents {
+    /// Build a nested heading tree from headings between `min_depth` and `max_depth`
+    pub fn build(headings: &[Heading], min_depth: u8, max_depth: u8) -> Vec<TocEntry> {
+        let mut entries = Vec::new();
+
+        for heading in headings {
+            if heading.level < min_depth || heading.level > max_depth {
+                continue;
+            }
+            let entry = TocEntry {
+                level: heading.level,
+                id: heading.id.clone(),
+                title: heading.title.clone(),
+                children: Vec::new(),
+            };
+            Self::insert(&mut entries, entry);
+        }
+
+        entries
+    }
+
+    /// Insert an entry beneath the last entry that is shallower than it
+    fn insert(entries: &mut Vec<TocEntry>, entry: TocEntry) {
+        match entries.last_mut() {
+            Some(last) if last.level < entry.level => Self::insert(&mut last.children, entry),
+            _ => entries.push(entry),
+        }
+    }
+
+    /// Render the table of contents as a navigation block
+    pub fn to_html(entries: &[TocEntry]) -> String {
+        if entries.is_empty() {
+            return String::new();
+        }
+
+        format!(
+            "<nav class=\"toc\" aria-label=\"Table of contents\">\n<p class=\"toc-title\">Contents</p>\n{}</nav>\n",
+            Self::render_list(entries)
+        )
+    }
+
+    fn render_list(entries: &[TocEntry]) -> String {
+        let items: String = entries
+            .iter()
+            .map(|entry| {
+                let children = if entry.children.is_empty() {
+                    String::new()
+                } else {
+                    format!("\n{}", Self::render_list(&entry.children))
+                };
+                format!(
+                    "<li><a href=\"#{}\">{}</a>{}</li>\n",
+                    HtmlUtils::escape(&entry.id),
+                    HtmlUtils::escape(&entry.title),
+                    children
+                )
+            })
+            .collect();
+
+        format!("<ul>\n{}</ul>\n", items)
+    }
+}
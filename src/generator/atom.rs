@@ -0,0 +1,107 @@
+use atom_syndication::{Content, Entry, Feed, Link, Person, Text};
+use crate::config::{Config, FeedFormat};
+use crate::content::Page;
+use super::feed::{FeedChannel, FeedGenerator};
+
+pub struct AtomGenerator;
+
+impl AtomGenerator {
+    /// Render an Atom 1.0 feed for a channel
+    pub fn render(channel: &FeedChannel, config: &Config) -> String {
+        let updated = channel.updated_or_now();
+
+        let mut feed = Feed::default();
+        feed.set_title(Text::plain(channel.title.clone()));
+        feed.set_id(channel.feed_url(FeedFormat::Atom, config));
+        feed.set_updated(updated);
+        feed.set_subtitle(Some(Text::plain(config.description.clone())));
+        feed.set_lang(Some(config.language.clone()));
+        feed.set_authors(Self::authors(config));
+        feed.set_links(vec![
+            Self::link(channel.feed_url(FeedFormat::Atom, config), "self", Some(FeedFormat::Atom.mime_type())),
+            Self::link(config.absolute_url("/"), "alternate", Some("text/html")),
+        ]);
+        feed.set_entries(
+            channel
+                .pages
+                .iter()
+                .map(|page| Self::build_entry(page, config, updated))
+                .collect::<Vec<_>>(),
+        );
+
+        feed.to_string()
+    }
+
+    /// Build an entry, updated at its `updated` or `date`; undated posts fall back to the
+    /// feed's update time
+    fn build_entry(page: &Page, config: &Config, feed_updated: atom_syndication::FixedDateTime) -> Entry {
+        let url = config.absolute_url(&page.url);
+
+        let mut entry = Entry::default();
+        entry.set_id(url.clone());
+        entry.set_title(Text::plain(page.title.clone()));
+        entry.set_updated(page.updated.or(page.date).unwrap_or(feed_updated));
+        entry.set_published(page.date);
+        entry.set_links(vec![Self::link(url, "alternate", Some("text/html"))]);
+        entry.set_summary(Some(Text::plain(page.summary.clone())));
+
+        if config.feed.full_content {
+            let mut content = Content::default();
+            content.set_content_type(Some("html".to_string()));
+            content.set_value(Some(FeedGenerator::entry_html(page, config)));
+            entry.set_content(Some(content));
+        }
+
+        entry
+    }
+
+    /// The configured author, else the blog title: Atom requires an author for every entry
+    /// and entries inherit the feed's
+    fn authors(config: &Config) -> Vec<Person> {
+        let mut person = Person::default();
+        person.set_name(config.author.clone().unwrap_or_else(|| config.blog_title.clone()));
+        vec![person]
+    }
+
+    fn link(href: String, rel: &str, mime_type: Option<&str>) -> Link {
+        let mut link = Link::default();
+        link.set_href(href);
+        link.set_rel(rel);
+        link.set_mime_type(mime_type.map(str::to_string));
+        link
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+
+    fn date(value: &str) -> atom_syndication::FixedDateTime {
+        DateTime::parse_from_rfc3339(value).unwrap()
+    }
+
+    #[test]
+    fn feed_author_falls_back_to_blog_title() {
+        let config = Config::default();
+        let page = Page { url: "/post.html".to_string(), ..Page::default() };
+        let channel = FeedChannel::new(config.blog_title.clone(), "", vec![&page], &config);
+
+        let feed: Feed = AtomGenerator::render(&channel, &config).parse().unwrap();
+        assert_eq!(feed.authors().len(), 1);
+        assert_eq!(feed.authors()[0].name(), config.blog_title);
+    }
+
+    #[test]
+    fn entry_updated_prefers_updated_front_matter() {
+        let config = Config::default();
+        let feed_updated = date("2025-03-01T00:00:00Z");
+        let mut page = Page { date: Some(date("2025-01-01T00:00:00Z")), ..Page::default() };
+
+        assert_eq!(*AtomGenerator::build_entry(&page, &config, feed_updated).updated(), date("2025-01-01T00:00:00Z"));
+        page.updated = Some(date("2025-02-01T00:00:00Z"));
+        let entry = AtomGenerator::build_entry(&page, &config, feed_updated);
+        assert_eq!(*entry.updated(), date("2025-02-01T00:00:00Z"));
+        assert_eq!(entry.published(), Some(&date("2025-01-01T00:00:00Z")));
+    }
+}
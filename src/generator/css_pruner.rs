@@ -0,0 +1,535 @@
+use anyhow::Result;
+use glob::glob;
+use scraper::{Html, Selector};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::config::CssConfig;
+
+/// At-rules holding nested rules that can be pruned; other at-rules are kept as written
+const GROUPING_AT_RULES: [&str; 4] = ["@media", "@supports", "@container", "@layer"];
+
+/// A top-level or nested piece of a stylesheet
+enum CssItem<'a> {
+    Rule { selectors: &'a str, body: &'a str },
+    Group { prelude: &'a str, items: Vec<CssItem<'a>> },
+    Verbatim(&'a str),
+}
+
+/// Class names, IDs and element names used by one or more pages
+#[derive(Default)]
+struct UsedNames {
+    classes: HashSet<String>,
+    ids: HashSet<String>,
+    elements: HashSet<String>,
+}
+
+impl UsedNames {
+    fn extend(&mut self, other: &UsedNames) {
+        self.classes.extend(other.classes.iter().cloned());
+        self.ids.extend(other.ids.iter().cloned());
+        self.elements.extend(other.elements.iter().cloned());
+    }
+}
+
+/// Rule counts and stylesheet sizes before and after pruning
+#[derive(Debug, Default)]
+pub struct PruneStats {
+    pub rules_removed: usize,
+    pub before: usize,
+    pub after: usize,
+    pub pages_inlined: usize,
+}
+
+pub struct CssPruner;
+
+impl CssPruner {
+    /// Remove CSS rules no generated page can match from every local stylesheet the pages
+    /// link to and, when enabled, inline each page's rules into its `<head>`. A stylesheet is
+    /// pruned against the pages linking it only, so a section's theme keeps just what that
+    /// section uses. Matching is by name, like PurgeCSS: a selector is kept when every class,
+    /// ID and element it names appears somewhere in the HTML, so states such as `:hover` and
+    /// combinators never cause a rule to be dropped. Names in the markup and DOM calls of the
+    /// local scripts a page loads count as used by the page, so elements `search.js` builds at
+    /// runtime keep their rules; names set any other way need `[css] safelist`.
+    pub fn run(output_dir: &str, config: &CssConfig) -> Result<PruneStats> {
+        let mut stats = PruneStats::default();
+
+        let mut pages = Vec::new();
+        let mut by_stylesheet: BTreeMap<String, UsedNames> = BTreeMap::new();
+        let mut scripts = BTreeMap::new();
+        for entry in glob(&format!("{}/**/*.html", output_dir))? {
+            let path = entry?;
+            let html = fs::read_to_string(&path)?;
+            let (used, stylesheets) = Self::scan(&html, output_dir, &mut scripts);
+            for stylesheet in &stylesheets {
+                by_stylesheet.entry(stylesheet.clone()).or_default().extend(&used);
+            }
+            pages.push((path, html, used, stylesheets));
+        }
+
+        let mut sources = BTreeMap::new();
+        for (url, site) in &by_stylesheet {
+            let stylesheet_path = format!("{}{}", output_dir, url);
+            let css = fs::read_to_string(&stylesheet_path)?;
+            stats.before += css.len();
+            let stripped = Self::strip_comments(&css);
+
+            if config.remove_unused {
+                let (pruned, removed) = Self::render(&Self::parse(&stripped), site, &config.safelist);
+                fs::write(&stylesheet_path, &pruned)?;
+                stats.rules_removed += removed;
+                stats.after += pruned.len();
+            } else {
+                stats.after += css.len();
+            }
+            sources.insert(url.as_str(), stripped);
+        }
+
+        // A page uses a subset of the names of all pages linking a stylesheet, so rendering
+        // from the unpruned rules gives the same result
+        if config.inline_critical {
+            let parsed: BTreeMap<&str, Vec<CssItem>> = sources.iter().map(|(url, css)| (*url, Self::parse(css))).collect();
+            for (path, html, used, stylesheets) in &pages {
+                let mut inlined = html.clone();
+                for url in stylesheets {
+                    let (critical, _) = Self::render(&parsed[url.as_str()], used, &config.safelist);
+                    inlined = Self::inline(&inlined, url, &critical).unwrap_or(inlined);
+                }
+                if inlined != *html {
+                    fs::write(path, inlined)?;
+                    stats.pages_inlined += 1;
+                }
+            }
+        }
+
+        Ok(stats)
+    }
+
+    /// Names a page and its local scripts use, and the local stylesheets it links to.
+    /// `scripts` caches the names found in each script by URL.
+    fn scan(html: &str, output_dir: &str, scripts: &mut BTreeMap<String, UsedNames>) -> (UsedNames, Vec<String>) {
+        let all = Selector::parse("*").expect("valid selector");
+        let links = Selector::parse(r#"link[rel~="stylesheet"][href]"#).expect("valid selector");
+        let script_tags = Selector::parse("script[src]").expect("valid selector");
+        let document = Html::parse_document(html);
+
+        let mut used = UsedNames::default();
+        for element in document.select(&all) {
+            let element = element.value();
+            used.elements.insert(element.name().to_ascii_lowercase());
+            used.classes.extend(element.classes().map(str::to_string));
+            if let Some(id) = element.id() {
+                used.ids.insert(id.to_string());
+            }
+        }
+
+        for script in document.select(&script_tags) {
+            let src = script.value().attr("src").unwrap_or_default();
+            if !src.starts_with('/') || src.starts_with("//") {
+                continue;
+            }
+            let names = scripts.entry(src.to_string()).or_insert_with(|| {
+                fs::read_to_string(format!("{}{}", output_dir, src))
+                    .map(|js| Self::script_names(&js))
+                    .unwrap_or_default()
+            });
+            used.extend(names);
+        }
+
+        let mut stylesheets: Vec<String> = Vec::new();
+        for link in document.select(&links) {
+            let href = link.value().attr("href").unwrap_or_default();
+            let local = href.starts_with('/') && !href.starts_with("//");
+            if local && Path::new(&format!("{}{}", output_dir, href)).is_file() && !stylesheets.iter().any(|url| url == href) {
+                stylesheets.push(href.to_string());
+            }
+        }
+        (used, stylesheets)
+    }
+
+    /// Names a script can put in the DOM: tags and `class`/`id` attributes in HTML it builds
+    /// from strings, and string arguments of `createElement` and `classList.add`
+    fn script_names(js: &str) -> UsedNames {
+        let mut names = UsedNames::default();
+        let quoted = |rest: &str| -> Vec<String> {
+            let mut values = Vec::new();
+            let mut rest = rest;
+            while let Some(quote) = rest.trim_start().chars().next().filter(|c| matches!(c, '"' | '\'' | '`')) {
+                let inner = &rest.trim_start()[1..];
+                let Some(end) = inner.find(quote) else { break };
+                values.push(inner[..end].to_string());
+                rest = inner[end + 1..].trim_start().strip_prefix(',').unwrap_or("");
+            }
+            values
+        };
+
+        for (index, _) in js.match_indices('<') {
+            let tag: String = js[index + 1..].chars().take_while(|c| c.is_ascii_alphanumeric() || *c == '-').collect();
+            if tag.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                names.elements.insert(tag.to_ascii_lowercase());
+            }
+        }
+        for (attribute, set) in [("class=", &mut names.classes), ("id=", &mut names.ids)] {
+            for (index, _) in js.match_indices(attribute) {
+                let value = js[index + attribute.len()..].trim_start_matches('\\');
+                let Some(quote) = value.chars().next().filter(|c| matches!(c, '"' | '\'')) else { continue };
+                let value = &value[1..];
+                let value = &value[..value.find(quote).unwrap_or(value.len())];
+                set.extend(value.trim_end_matches('\\').split_whitespace().map(str::to_string));
+            }
+        }
+        for (index, _) in js.match_indices("createElement(") {
+            names.elements.extend(quoted(&js[index + "createElement(".len()..]).iter().map(|tag| tag.to_ascii_lowercase()));
+        }
+        for (index, _) in js.match_indices("classList.add(") {
+            names.classes.extend(quoted(&js[index + "classList.add(".len()..]));
+        }
+        names
+    }
+
+    /// Put the page's rules in a `<style>` before the stylesheet link and turn the link into
+    /// a preload that applies once loaded. `None` when the page doesn't load the stylesheet
+    /// with a plain link, as with the disabled stylesheets of a theme toggle, or uses none of
+    /// its rules.
+    fn inline(html: &str, url: &str, critical: &str) -> Option<String> {
+        let link = format!("<link rel=\"stylesheet\" href=\"{}\">", url);
+        if !html.contains(&link) || critical.trim().is_empty() {
+            return None;
+        }
+        let deferred = format!(
+            "<style>{}</style>\n    <link rel=\"preload\" href=\"{url}\" as=\"style\" onload=\"this.onload=null;this.rel='stylesheet'\">\n    <noscript><link rel=\"stylesheet\" href=\"{url}\"></noscript>",
+            critical.trim(),
+            url = url
+        );
+        Some(html.replacen(&link, &deferred, 1))
+    }
+
+    /// Serialize the items whose selectors can match `used`, returning the CSS and how many
+    /// rules were left out
+    fn render(items: &[CssItem], used: &UsedNames, safelist: &[String]) -> (String, usize) {
+        let mut css = String::new();
+        let mut removed = 0;
+        for item in items {
+            match item {
+                CssItem::Rule { selectors, body } => {
+                    let kept: Vec<&str> = Self::split_top_level(selectors)
+                        .into_iter()
+                        .filter(|selector| Self::can_match(selector, used, safelist))
+                        .collect();
+                    if kept.is_empty() {
+                        removed += 1;
+                    } else {
+                        css.push_str(&format!("{} {{{}}}\n\n", kept.join(",\n"), body));
+                    }
+                }
+                CssItem::Group { prelude, items } => {
+                    let (inner, inner_removed) = Self::render(items, used, safelist);
+                    removed += inner_removed;
+                    if !inner.trim().is_empty() {
+                        css.push_str(&format!("{} {{\n{}}}\n\n", prelude, inner));
+                    }
+                }
+                CssItem::Verbatim(text) => {
+                    css.push_str(text);
+                    css.push_str("\n\n");
+                }
+            }
+        }
+        (css, removed)
+    }
+
+    /// Whether every class, ID and element a selector names is used or safelisted.
+    /// Attribute selectors and the arguments of functional pseudo-classes such as `:not()`
+    /// are ignored, which errs on the side of keeping rules.
+    fn can_match(selector: &str, used: &UsedNames, safelist: &[String]) -> bool {
+        let safe = |name: &str| {
+            safelist.iter().any(|entry| match entry.strip_suffix('*') {
+                Some(prefix) => name.starts_with(prefix),
+                None => entry == name,
+            })
+        };
+
+        let mut chars = selector.chars().peekable();
+        let mut compound_start = true;
+        while let Some(c) = chars.next() {
+            match c {
+                '.' | '#' => {
+                    let name = Self::read_ident(&mut chars);
+                    let found = if c == '.' { used.classes.contains(&name) } else { used.ids.contains(&name) };
+                    if !found && !safe(&name) {
+                        return false;
+                    }
+                    compound_start = false;
+                }
+                ':' => {
+                    if chars.peek() == Some(&':') {
+                        chars.next();
+                    }
+                    Self::read_ident(&mut chars);
+                    if chars.peek() == Some(&'(') {
+                        Self::skip_block(&mut chars, '(', ')');
+                    }
+                    compound_start = false;
+                }
+                '[' => {
+                    Self::skip_block(&mut chars, '[', ']');
+                    compound_start = false;
+                }
+                ' ' | '\t' | '\n' | '>' | '+' | '~' => compound_start = true,
+                _ if compound_start && (c.is_alphabetic() || c == '-' || c == '_') => {
+                    let mut name = c.to_string();
+                    name.push_str(&Self::read_ident(&mut chars));
+                    let name = name.to_ascii_lowercase();
+                    if !used.elements.contains(&name) && !safe(&name) {
+                        return false;
+                    }
+                    compound_start = false;
+                }
+                _ => compound_start = false,
+            }
+        }
+        true
+    }
+
+    /// Read a CSS identifier, unescaping `\` sequences
+    fn read_ident(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+        let mut ident = String::new();
+        while let Some(&c) = chars.peek() {
+            if c == '\\' {
+                chars.next();
+                if let Some(escaped) = chars.next() {
+                    ident.push(escaped);
+                }
+            } else if c.is_alphanumeric() || c == '-' || c == '_' || !c.is_ascii() {
+                ident.push(c);
+                chars.next();
+            } else {
+                break;
+            }
+        }
+        ident
+    }
+
+    /// Skip past the bracket closing the one just consumed, allowing nesting
+    fn skip_block(chars: &mut std::iter::Peekable<std::str::Chars>, open: char, close: char) {
+        let mut depth = 0;
+        for c in chars.by_ref() {
+            if c == open {
+                depth += 1;
+            } else if c == close {
+                if depth <= 1 {
+                    return;
+                }
+                depth -= 1;
+            }
+        }
+    }
+
+    /// Split a selector list on commas outside parentheses and brackets
+    fn split_top_level(selectors: &str) -> Vec<&str> {
+        let mut parts = Vec::new();
+        let mut depth = 0;
+        let mut start = 0;
+        for (index, c) in selectors.char_indices() {
+            match c {
+                '(' | '[' => depth += 1,
+                ')' | ']' => depth -= 1,
+                ',' if depth == 0 => {
+                    parts.push(selectors[start..index].trim());
+                    start = index + 1;
+                }
+                _ => {}
+            }
+        }
+        parts.push(selectors[start..].trim());
+        parts.into_iter().filter(|part| !part.is_empty()).collect()
+    }
+
+    /// Remove `/* … */` comments outside strings
+    fn strip_comments(css: &str) -> String {
+        let mut out = String::with_capacity(css.len());
+        let mut chars = css.chars().peekable();
+        let mut quote = None;
+        while let Some(c) = chars.next() {
+            match quote {
+                Some(open) => {
+                    out.push(c);
+                    if c == '\\' {
+                        if let Some(escaped) = chars.next() {
+                            out.push(escaped);
+                        }
+                    } else if c == open {
+                        quote = None;
+                    }
+                }
+                None if c == '/' && chars.peek() == Some(&'*') => {
+                    chars.next();
+                    let mut previous = ' ';
+                    for comment in chars.by_ref() {
+                        if previous == '*' && comment == '/' {
+                            break;
+                        }
+                        previous = comment;
+                    }
+                }
+                None => {
+                    if c == '"' || c == '\'' {
+                        quote = Some(c);
+                    }
+                    out.push(c);
+                }
+            }
+        }
+        out
+    }
+
+    /// Split comment-free CSS into rules, grouping at-rules and verbatim at-rules
+    fn parse(css: &str) -> Vec<CssItem<'_>> {
+        let mut items = Vec::new();
+        let mut rest = css.trim_start();
+
+        while !rest.is_empty() {
+            let Some((open, delimiter)) = Self::find_top_level(rest, &['{', ';']) else {
+                items.push(CssItem::Verbatim(rest.trim()));
+                break;
+            };
+            if delimiter == ';' {
+                // `@import`, `@charset` and other statements
+                items.push(CssItem::Verbatim(rest[..=open].trim()));
+                rest = rest[open + 1..].trim_start();
+                continue;
+            }
+
+            let close = Self::matching_brace(rest, open);
+            let prelude = rest[..open].trim();
+            let body = &rest[open + 1..close.min(rest.len())];
+            let end = (close + 1).min(rest.len());
+
+            if prelude.starts_with('@') {
+                let name = prelude.split(|c: char| c.is_whitespace() || c == '(').next().unwrap_or("");
+                if GROUPING_AT_RULES.contains(&name.to_ascii_lowercase().as_str()) {
+                    items.push(CssItem::Group { prelude, items: Self::parse(body) });
+                } else {
+                    items.push(CssItem::Verbatim(rest[..end].trim()));
+                }
+            } else {
+                items.push(CssItem::Rule { selectors: prelude, body });
+            }
+            rest = rest[end..].trim_start();
+        }
+
+        items
+    }
+
+    /// Byte offset of the first of `delimiters` outside strings and brackets
+    fn find_top_level(css: &str, delimiters: &[char]) -> Option<(usize, char)> {
+        let mut quote = None;
+        let mut depth = 0;
+        let mut escaped = false;
+        for (index, c) in css.char_indices() {
+            if let Some(open) = quote {
+                if !escaped && c == open {
+                    quote = None;
+                }
+                escaped = c == '\\' && !escaped;
+                continue;
+            }
+            match c {
+                '"' | '\'' => quote = Some(c),
+                '(' | '[' => depth += 1,
+                ')' | ']' => depth -= 1,
+                _ if depth == 0 && delimiters.contains(&c) => return Some((index, c)),
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Byte offset of the `}` closing the `{` at `open`, or the end of the text
+    fn matching_brace(css: &str, open: usize) -> usize {
+        let mut depth = 0;
+        let mut quote = None;
+        let mut escaped = false;
+        for (index, c) in css[open..].char_indices() {
+            if let Some(start) = quote {
+                if !escaped && c == start {
+                    quote = None;
+                }
+                escaped = c == '\\' && !escaped;
+                continue;
+            }
+            match c {
+                '"' | '\'' => quote = Some(c),
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return open + index;
+                    }
+                }
+                _ => {}
+            }
+        }
+        css.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TestDir;
+
+    #[test]
+    fn scans_names_and_local_stylesheets() {
+        let dir = TestDir::new("css");
+        dir.write("assets/theme-hacker.css", "");
+        let html = concat!(
+            r#"<html><head><link rel="stylesheet" href="/assets/theme-hacker.css">"#,
+            r#"<link rel="stylesheet" href="/assets/missing.css"><link rel="stylesheet" href="https://cdn.example.com/x.css">"#,
+            r#"</head><body><main id="top" class="container wide"></main></body></html>"#
+        );
+
+        let (used, stylesheets) = CssPruner::scan(html, &dir.path().to_string_lossy(), &mut BTreeMap::new());
+        assert_eq!(stylesheets, ["/assets/theme-hacker.css"]);
+        assert!(used.classes.contains("wide") && used.ids.contains("top") && used.elements.contains("main"));
+    }
+
+    #[test]
+    fn names_built_by_local_scripts_count_as_used() {
+        let dir = TestDir::new("css-scripts");
+        dir.write("assets/search.js", include_str!("../../themes/search.js"));
+        dir.write("assets/toast.js", "var el = document.createElement('aside'); el.classList.add('toast', \"toast-open\");");
+        let html = r#"<html><head><script src="/assets/search.js" defer></script><script src="/assets/toast.js"></script></head><body></body></html>"#;
+
+        let (used, _) = CssPruner::scan(html, &dir.path().to_string_lossy(), &mut BTreeMap::new());
+        for element in ["li", "a", "time", "mark", "aside"] {
+            assert!(used.elements.contains(element), "{}", element);
+        }
+        for class in ["search-result", "toast", "toast-open"] {
+            assert!(used.classes.contains(class), "{}", class);
+        }
+    }
+
+    #[test]
+    fn prunes_rules_naming_unused_classes() {
+        let used = UsedNames {
+            classes: HashSet::from(["post".to_string()]),
+            elements: HashSet::from(["a".to_string()]),
+            ..UsedNames::default()
+        };
+        let css = ".post a:hover { color: red; } .unused { color: blue; } @media (min-width: 1px) { .unused, .post { margin: 0; } }";
+        let (pruned, removed) = CssPruner::render(&CssPruner::parse(css), &used, &[]);
+        assert_eq!(removed, 1);
+        assert!(pruned.contains(".post a:hover") && !pruned.contains("color: blue"));
+    }
+
+    #[test]
+    fn inlines_only_plain_links_with_rules() {
+        let html = r#"<link rel="stylesheet" href="/assets/style.css"><link rel="stylesheet" href="/assets/theme-hacker.css" data-theme="hacker" disabled>"#;
+        assert!(CssPruner::inline(html, "/assets/style.css", "body{margin:0}").unwrap().starts_with("<style>body{margin:0}</style>"));
+        assert_eq!(CssPruner::inline(html, "/assets/style.css", " "), None);
+        assert_eq!(CssPruner::inline(html, "/assets/theme-hacker.css", "body{margin:0}"), None);
+    }
+}
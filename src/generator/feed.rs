@@ -0,0 +1,166 @@
+use anyhow::Result;
+use chrono::{DateTime, FixedOffset, Utc};
+use std::collections::BTreeMap;
+use std::fs;
+
+use super::atom::AtomGenerator;
+use super::json_feed::JsonFeedGenerator;
+use super::rss::RssGenerator;
+use crate::config::{Config, FeedFormat};
+use crate::content::{ContentProcessor, Page};
+use crate::utils::{HtmlUtils, SlugGenerator};
+
+/// A set of posts published together as one feed in every configured format
+pub struct FeedChannel<'a> {
+    pub title: String,
+    /// Site-relative directory the feed files are written to (`""` for the site root)
+    pub path: String,
+    /// Posts in the feed, newest first
+    pub pages: Vec<&'a Page>,
+    /// Whether this is the podcast feed, which adds enclosures and iTunes tags to its RSS
+    pub podcast: bool,
+}
+
+impl<'a> FeedChannel<'a> {
+    /// Create a channel from the given posts, sorted newest first and limited to `[feed] limit`
+    pub fn new(title: String, path: &str, mut pages: Vec<&'a Page>, config: &Config) -> Self {
+        // Newest first, undated posts last
+        pages.sort_by_key(|page| std::cmp::Reverse(page.date));
+        if config.feed.limit > 0 {
+            pages.truncate(config.feed.limit);
+        }
+
+        Self {
+            title,
+            path: path.trim_end_matches('/').to_string(),
+            pages,
+            podcast: false,
+        }
+    }
+
+    /// Absolute URL of this channel in the given format
+    pub fn feed_url(&self, format: FeedFormat, config: &Config) -> String {
+        config.absolute_url(&format!("{}/{}", self.path, format.file_name()))
+    }
+
+    /// Latest `updated` or `date` of the posts in the channel
+    pub fn updated(&self) -> Option<DateTime<FixedOffset>> {
+        self.pages.iter().filter_map(|page| page.updated.or(page.date)).max()
+    }
+
+    /// Latest post date, or the build time when no post is dated
+    pub fn updated_or_now(&self) -> DateTime<FixedOffset> {
+        self.updated().unwrap_or_else(|| Utc::now().fixed_offset())
+    }
+}
+
+pub struct FeedGenerator;
+
+impl FeedGenerator {
+    /// Generate the site-wide feed, one feed per configured section and one per tag,
+    /// each in every configured format. Returns the site-relative paths of the files written.
+    pub fn generate_feeds(output_dir: &str, pages: &[Page], config: &Config) -> Result<Vec<String>> {
+        let mut written = Vec::new();
+
+        let feedable: Vec<&Page> = pages
+            .iter()
+            .filter(|page| config.feed.sections.contains(&page.section))
+            .collect();
+
+        let site_channel = FeedChannel::new(config.blog_title.clone(), "", feedable.clone(), config);
+        written.extend(Self::write_channel(output_dir, &site_channel, config)?);
+
+        for section in config.feed.sections.iter().filter(|section| !section.is_empty()) {
+            if config.podcast.section.as_ref() == Some(section) {
+                continue;
+            }
+            let posts = feedable.iter().copied().filter(|page| &page.section == section).collect();
+            let title = format!("{} - {}", config.blog_title, section);
+            let channel = FeedChannel::new(title, &format!("/{}", section), posts, config);
+            written.extend(Self::write_channel(output_dir, &channel, config)?);
+        }
+
+        // The podcast section always gets a feed, whether or not it's listed in `[feed] sections`
+        if let Some(section) = config.podcast.section.as_ref().filter(|section| !section.is_empty()) {
+            let episodes = pages
+                .iter()
+                .filter(|page| &page.section == section && page.episode.is_some())
+                .collect();
+            let title = format!("{} - {}", config.blog_title, section);
+            let mut channel = FeedChannel::new(title, &format!("/{}", section), episodes, config);
+            channel.podcast = true;
+            written.extend(Self::write_channel(output_dir, &channel, config)?);
+        }
+
+        if config.feed.tag_feeds {
+            for (slug, (tag, posts)) in Self::group_by_tag(pages) {
+                let title = format!("{} - {}", config.blog_title, tag);
+                let channel = FeedChannel::new(title, &format!("/tags/{}", slug), posts, config);
+                written.extend(Self::write_channel(output_dir, &channel, config)?);
+            }
+        }
+
+        Ok(written)
+    }
+
+    /// Group pages by tag slug, keeping the first spelling of each tag for display
+    fn group_by_tag(pages: &[Page]) -> BTreeMap<String, (String, Vec<&Page>)> {
+        let mut tags: BTreeMap<String, (String, Vec<&Page>)> = BTreeMap::new();
+
+        for page in pages {
+            for tag in &page.tags {
+                let slug = SlugGenerator::slugify(tag);
+                if slug.is_empty() {
+                    continue;
+                }
+                let (_, posts) = tags.entry(slug).or_insert_with(|| (tag.clone(), Vec::new()));
+                // Tags that only differ in spelling shouldn't list a page twice
+                if !posts.last().is_some_and(|last| std::ptr::eq(*last, page)) {
+                    posts.push(page);
+                }
+            }
+        }
+
+        tags
+    }
+
+    /// Formats a channel is written in. Podcast apps only read RSS,
+    /// so the podcast channel is always written as RSS too.
+    pub fn channel_formats(podcast: bool, config: &Config) -> Vec<FeedFormat> {
+        let mut formats = config.feed.formats.clone();
+        if podcast && !formats.contains(&FeedFormat::Rss) {
+            formats.insert(0, FeedFormat::Rss);
+        }
+        formats
+    }
+
+    /// Write one channel in each of its formats
+    fn write_channel(output_dir: &str, channel: &FeedChannel, config: &Config) -> Result<Vec<String>> {
+        let mut written = Vec::new();
+
+        for format in &Self::channel_formats(channel.podcast, config) {
+            let content = match format {
+                FeedFormat::Rss => RssGenerator::render(channel, config),
+                FeedFormat::Atom => AtomGenerator::render(channel, config),
+                FeedFormat::Json => JsonFeedGenerator::render(channel, config)?,
+            };
+
+            let feed_path = format!("{}/{}", channel.path, format.file_name());
+            let output_path = format!("{}{}", output_dir, feed_path);
+            ContentProcessor::ensure_output_dir(&output_path)?;
+            fs::write(&output_path, content)?;
+            written.push(feed_path);
+        }
+
+        Ok(written)
+    }
+
+    /// Entry body: the full post or its summary, with links made absolute
+    pub fn entry_html(page: &Page, config: &Config) -> String {
+        if config.feed.full_content {
+            HtmlUtils::absolutize_urls(&page.content, &config.base_url, &page.url)
+        } else {
+            page.summary_html(&config.base_url)
+        }
+    }
+}
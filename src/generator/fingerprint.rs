@@ -0,0 +1,304 @@
+use anyhow::Result;
+use glob::glob;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::utils::HashUtils;
+
+/// Written to the output root, mapping each asset URL to its fingerprinted URL
+pub const MANIFEST_FILE: &str = "assets-manifest.json";
+
+/// Length of the content hash added to file names
+const HASH_LENGTH: usize = 8;
+
+/// HTML attributes whose values are URLs (`srcset` holds a list of them)
+const URL_ATTRIBUTES: &[&str] = &["src", "href", "srcset", "poster"];
+
+/// A renamed asset and its Subresource Integrity value
+struct Fingerprinted {
+    url: String,
+    integrity: String,
+}
+
+pub struct AssetFingerprinter;
+
+impl AssetFingerprinter {
+    /// Rename every file under `/assets/` to include a hash of its content
+    /// (`style.css` → `style.3fa9c1d2.css`), rewrite references to it in HTML and CSS, add
+    /// `integrity` to the `<link>` and `<script>` tags loading it and write the manifest.
+    /// Social cards already carry a content hash and are left alone. Files listed in
+    /// `prehashed`, such as image variants named after their source's hash, keep their name
+    /// but are listed in the manifest too. Returns how many assets were renamed.
+    pub fn fingerprint(output_dir: &str, prehashed: &[&str]) -> Result<usize> {
+        let mut files: Vec<PathBuf> = glob(&format!("{}/assets/**/*", output_dir))?
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .filter(|path| path.is_file() && !Self::is_fingerprinted(path))
+            .filter(|path| !Self::url_for(output_dir, path).starts_with("/assets/social/"))
+            .collect();
+        // Stylesheets go last so the assets they reference are renamed before they are hashed
+        files.sort_by_key(|path| path.extension().is_some_and(|extension| extension == "css"));
+
+        let mut assets: BTreeMap<String, Fingerprinted> = BTreeMap::new();
+        for path in files {
+            if path.extension().is_some_and(|extension| extension == "css") {
+                let css = fs::read_to_string(&path)?;
+                fs::write(&path, Self::rewrite_css(&css, &assets))?;
+            }
+
+            let data = fs::read(&path)?;
+            let hash = &HashUtils::sha256_hex(&data)[..HASH_LENGTH];
+            let hashed_path = Self::hashed_path(&path, hash);
+            Self::remove_stale(&path)?;
+            fs::rename(&path, &hashed_path)?;
+
+            assets.insert(
+                Self::url_for(output_dir, &path),
+                Fingerprinted {
+                    url: Self::url_for(output_dir, &hashed_path),
+                    integrity: HashUtils::sri_sha384(&data),
+                },
+            );
+        }
+
+        let renamed = assets.len();
+        for url in prehashed.iter().filter(|url| url.starts_with("/assets/")) {
+            let path = Path::new(output_dir).join(url.trim_start_matches('/'));
+            let (Some(unhashed), Ok(data)) = (Self::unhashed_name(&path), fs::read(&path)) else {
+                continue;
+            };
+            assets.insert(
+                Self::url_for(output_dir, &path.with_file_name(unhashed)),
+                Fingerprinted { url: url.to_string(), integrity: HashUtils::sri_sha384(&data) },
+            );
+        }
+
+        for entry in glob(&format!("{}/**/*.html", output_dir))? {
+            let path = entry?;
+            let html = Self::rewrite_html(&fs::read_to_string(&path)?, &assets);
+            fs::write(&path, Self::add_integrity(&html, &assets))?;
+        }
+
+        let manifest: BTreeMap<&str, &str> = assets.iter().map(|(url, asset)| (url.as_str(), asset.url.as_str())).collect();
+        fs::write(Path::new(output_dir).join(MANIFEST_FILE), serde_json::to_string_pretty(&manifest)?)?;
+
+        Ok(renamed)
+    }
+
+    /// `name.ext` → `name.<hash>.ext`
+    fn hashed_path(path: &Path, hash: &str) -> PathBuf {
+        let name = match (path.file_stem(), path.extension()) {
+            (Some(stem), Some(extension)) => {
+                format!("{}.{}.{}", stem.to_string_lossy(), hash, extension.to_string_lossy())
+            }
+            _ => format!("{}.{}", path.file_name().unwrap_or_default().to_string_lossy(), hash),
+        };
+        path.with_file_name(name)
+    }
+
+    /// Whether a file name already carries a content hash, e.g. left over from an earlier build
+    fn is_fingerprinted(path: &Path) -> bool {
+        Self::unhashed_name(path).is_some()
+    }
+
+    /// `name.<hash>.ext` → `name.ext`, `None` for names without a hash
+    fn unhashed_name(path: &Path) -> Option<String> {
+        let stem = path.file_stem()?.to_str()?;
+        let (name, hash) = stem.rsplit_once('.')?;
+        if hash.len() != HASH_LENGTH || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        Some(match path.extension() {
+            Some(extension) => format!("{}.{}", name, extension.to_string_lossy()),
+            None => name.to_string(),
+        })
+    }
+
+    /// Delete fingerprinted copies of an asset from earlier builds
+    fn remove_stale(path: &Path) -> Result<()> {
+        let (Some(directory), Some(name)) = (path.parent(), path.file_name()) else {
+            return Ok(());
+        };
+        for entry in fs::read_dir(directory)? {
+            let old = entry?.path();
+            if Self::unhashed_name(&old).is_some_and(|unhashed| unhashed == name.to_string_lossy()) {
+                fs::remove_file(old)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Site-relative URL of a file in the output directory
+    fn url_for(output_dir: &str, path: &Path) -> String {
+        let relative = path.strip_prefix(output_dir).unwrap_or(path);
+        let parts: Vec<String> = relative.components().map(|part| part.as_os_str().to_string_lossy().into_owned()).collect();
+        format!("/{}", parts.join("/"))
+    }
+
+    /// Replace quoted (`@import "…"`) and `url()` references to renamed assets in CSS
+    fn rewrite_css(css: &str, assets: &BTreeMap<String, Fingerprinted>) -> String {
+        let mut rewritten = css.to_string();
+        for (url, asset) in assets {
+            for (open, close) in [("\"", "\""), ("'", "'"), ("(", ")")] {
+                rewritten = rewritten.replace(&format!("{}{}{}", open, url, close), &format!("{}{}{}", open, asset.url, close));
+            }
+        }
+        rewritten
+    }
+
+    /// Replace references to renamed assets in the URL attributes of HTML tags, every
+    /// candidate of a `srcset` included, and in `url()` of inline styles
+    fn rewrite_html(html: &str, assets: &BTreeMap<String, Fingerprinted>) -> String {
+        let mut result = String::with_capacity(html.len());
+        let mut rest = html;
+        while let Some(equals) = rest.find('=') {
+            let name = rest[..equals]
+                .rsplit(|c: char| c.is_ascii_whitespace())
+                .next()
+                .unwrap_or_default()
+                .to_ascii_lowercase();
+            let quote = rest[equals + 1..].chars().next().filter(|quote| *quote == '"' || *quote == '\'');
+            let value_start = equals + 2;
+            let value_end = quote.and_then(|quote| rest[value_start..].find(quote)).map(|end| value_start + end);
+
+            match value_end {
+                Some(value_end) if URL_ATTRIBUTES.contains(&name.as_str()) => {
+                    let value = &rest[value_start..value_end];
+                    result.push_str(&rest[..value_start]);
+                    if name == "srcset" {
+                        result.push_str(&Self::rewrite_srcset(value, assets));
+                    } else {
+                        result.push_str(&Self::rewrite_url(value, assets));
+                    }
+                    rest = &rest[value_end..];
+                }
+                _ => {
+                    result.push_str(&rest[..=equals]);
+                    rest = &rest[equals + 1..];
+                }
+            }
+        }
+        result.push_str(rest);
+
+        for (url, asset) in assets {
+            for (open, close) in [("url(", ")"), ("url(\"", "\")"), ("url('", "')")] {
+                result = result.replace(&format!("{}{}{}", open, url, close), &format!("{}{}{}", open, asset.url, close));
+            }
+        }
+        result
+    }
+
+    /// `srcset` candidates are a URL and an optional width or density, separated by commas
+    fn rewrite_srcset(srcset: &str, assets: &BTreeMap<String, Fingerprinted>) -> String {
+        srcset
+            .split(',')
+            .map(|candidate| {
+                let start = candidate.len() - candidate.trim_start().len();
+                let end = candidate[start..].find(char::is_whitespace).map_or(candidate.len(), |end| start + end);
+                format!("{}{}{}", &candidate[..start], Self::rewrite_url(&candidate[start..end], assets), &candidate[end..])
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// The fingerprinted URL of an asset, keeping any query or fragment
+    fn rewrite_url(url: &str, assets: &BTreeMap<String, Fingerprinted>) -> String {
+        let split = url.find(['?', '#']).unwrap_or(url.len());
+        match assets.get(&url[..split]) {
+            Some(asset) => format!("{}{}", asset.url, &url[split..]),
+            None => url.to_string(),
+        }
+    }
+
+    /// Add `integrity` and `crossorigin` to `<link>` and `<script>` tags loading a fingerprinted asset
+    fn add_integrity(html: &str, assets: &BTreeMap<String, Fingerprinted>) -> String {
+        let integrity: BTreeMap<&str, &str> =
+            assets.values().map(|asset| (asset.url.as_str(), asset.integrity.as_str())).collect();
+
+        let mut result = String::with_capacity(html.len());
+        let mut rest = html;
+        while let Some(start) = rest.find("<link ").into_iter().chain(rest.find("<script ")).min() {
+            let end = rest[start..].find('>').map_or(rest.len(), |end| start + end);
+            let tag = &rest[start..end];
+            result.push_str(&rest[..start]);
+
+            let loaded = ["href=\"", "src=\""]
+                .iter()
+                .find_map(|attribute| tag.split_once(attribute))
+                .and_then(|(_, value)| value.split('"').next())
+                .and_then(|url| integrity.get(url));
+            match loaded {
+                Some(hash) if !tag.contains(" integrity=") => {
+                    let (tag, self_closing) = match tag.strip_suffix('/') {
+                        Some(tag) => (tag.trim_end(), " /"),
+                        None => (tag, ""),
+                    };
+                    result.push_str(&format!("{} integrity=\"{}\" crossorigin=\"anonymous\"{}", tag, hash, self_closing));
+                }
+                _ => result.push_str(tag),
+            }
+            rest = &rest[end..];
+        }
+
+        result.push_str(rest);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assets() -> BTreeMap<String, Fingerprinted> {
+        [("/assets/style.css", "/assets/style.1a2b3c4d.css"), ("/assets/img/photo.png", "/assets/img/photo.6e10e5cd.png")]
+            .into_iter()
+            .map(|(url, hashed)| (url.to_string(), Fingerprinted { url: hashed.to_string(), integrity: String::new() }))
+            .collect()
+    }
+
+    #[test]
+    fn rewrites_every_srcset_candidate() {
+        let html = r#"<img src="/assets/img/photo.png" srcset="/assets/img/photo-480w.6e10e5cd.png 480w, /assets/img/photo.png 600w">"#;
+        assert_eq!(
+            AssetFingerprinter::rewrite_html(html, &assets()),
+            r#"<img src="/assets/img/photo.6e10e5cd.png" srcset="/assets/img/photo-480w.6e10e5cd.png 480w, /assets/img/photo.6e10e5cd.png 600w">"#
+        );
+    }
+
+    #[test]
+    fn rewrites_url_attributes_only() {
+        let html = concat!(
+            r#"<link rel="stylesheet" href='/assets/style.css?v=2'>"#,
+            r#"<div data-src="/assets/style.css" style="background: url(/assets/img/photo.png)">"#,
+            r#"<code>"/assets/style.css"</code>"#
+        );
+        assert_eq!(
+            AssetFingerprinter::rewrite_html(html, &assets()),
+            concat!(
+                r#"<link rel="stylesheet" href='/assets/style.1a2b3c4d.css?v=2'>"#,
+                r#"<div data-src="/assets/style.css" style="background: url(/assets/img/photo.6e10e5cd.png)">"#,
+                r#"<code>"/assets/style.css"</code>"#
+            )
+        );
+    }
+
+    #[test]
+    fn rewrites_css_imports_and_urls() {
+        let css = r#"@import "/assets/style.css"; body { background: url('/assets/img/photo.png'); }"#;
+        assert_eq!(
+            AssetFingerprinter::rewrite_css(css, &assets()),
+            r#"@import "/assets/style.1a2b3c4d.css"; body { background: url('/assets/img/photo.6e10e5cd.png'); }"#
+        );
+    }
+
+    #[test]
+    fn recognizes_hashed_names() {
+        assert_eq!(AssetFingerprinter::unhashed_name(Path::new("photo-480w.6e10e5cd.avif")).as_deref(), Some("photo-480w.avif"));
+        assert_eq!(AssetFingerprinter::unhashed_name(Path::new("jquery.min.js")), None);
+        assert_eq!(
+            AssetFingerprinter::hashed_path(Path::new("/out/assets/style.css"), "1a2b3c4d"),
+            Path::new("/out/assets/style.1a2b3c4d.css")
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use anyhow::{Context, Result};
+use image::codecs::avif::AvifEncoder;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageEncoder};
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use crate::config::{Config, ImageFormat};
+use crate::content::images::ImageVariant;
+use crate::content::{ContentProcessor, Page};
+
+/// rav1e speed from 1 (slowest, smallest) to 10; AVIF encoding dominates build time otherwise
+const AVIF_SPEED: u8 = 8;
+
+pub struct ImageProcessor;
+
+impl ImageProcessor {
+    /// Encode every image variant the pages refer to and copy it into the output directory.
+    /// Variants are kept in `[images] cache_dir`, so unchanged images are only encoded once.
+    /// Returns how many variants were encoded and how many came from the cache.
+    pub fn process(pages: &[Page], config: &Config, output_dir: &str) -> Result<(usize, usize)> {
+        // Group by source so each image is decoded at most once
+        let mut seen = HashSet::new();
+        let mut by_source: BTreeMap<&PathBuf, Vec<&ImageVariant>> = BTreeMap::new();
+        for variant in pages.iter().flat_map(|page| &page.images) {
+            if seen.insert(&variant.url) {
+                by_source.entry(&variant.source).or_default().push(variant);
+            }
+        }
+
+        let cache_dir = Path::new(&config.images.cache_dir);
+        let (mut encoded, mut cached) = (0, 0);
+        for (source, variants) in by_source {
+            let mut decoded: Option<DynamicImage> = None;
+            for variant in variants {
+                let cache_path = cache_dir.join(&variant.cache_name);
+                if cache_path.exists() {
+                    cached += 1;
+                } else {
+                    if decoded.is_none() {
+                        decoded = Some(image::open(source).with_context(|| format!("Failed to decode image {}", source.display()))?);
+                    }
+                    let resized = decoded
+                        .as_ref()
+                        .expect("decoded above")
+                        .resize_exact(variant.width, variant.height, FilterType::Lanczos3);
+                    fs::create_dir_all(cache_dir)?;
+                    Self::encode(&resized, variant.format, variant.quality, &cache_path)
+                        .with_context(|| format!("Failed to encode {}", variant.url))?;
+                    encoded += 1;
+                }
+
+                let output_path = format!("{}{}", output_dir, variant.url);
+                ContentProcessor::ensure_output_dir(&output_path)?;
+                fs::copy(&cache_path, &output_path)?;
+            }
+        }
+
+        Ok((encoded, cached))
+    }
+
+    fn encode(image: &DynamicImage, format: ImageFormat, quality: Option<u8>, path: &Path) -> Result<()> {
+        // Write to a temporary file first so an interrupted build never leaves a broken cache entry
+        let partial = path.with_extension(format!("{}.partial", format.extension()));
+        let writer = BufWriter::new(File::create(&partial)?);
+
+        // Encoders take 8-bit RGB(A); JPEG has no alpha channel
+        let (bytes, color) = if image.color().has_alpha() && format != ImageFormat::Jpeg {
+            (image.to_rgba8().into_raw(), image::ExtendedColorType::Rgba8)
+        } else {
+            (image.to_rgb8().into_raw(), image::ExtendedColorType::Rgb8)
+        };
+        let (width, height) = (image.width(), image.height());
+
+        let quality = quality.map(|quality| quality.clamp(1, 100));
+
+        match (format, quality) {
+            (ImageFormat::Avif, quality) => AvifEncoder::new_with_speed_quality(writer, AVIF_SPEED, quality.unwrap_or(100)).write_image(&bytes, width, height, color)?,
+            (ImageFormat::Webp, _) => WebPEncoder::new_lossless(writer).write_image(&bytes, width, height, color)?,
+            (ImageFormat::Jpeg, quality) => JpegEncoder::new_with_quality(writer, quality.unwrap_or(100)).write_image(&bytes, width, height, color)?,
+            (ImageFormat::Png, _) => PngEncoder::new(writer).write_image(&bytes, width, height, color)?,
+        }
+
+        fs::rename(&partial, path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TestDir;
+    use image::RgbaImage;
+
+    #[test]
+    fn webp_copies_are_lossless() {
+        let dir = TestDir::new("images");
+        let path = dir.path().join("chart.webp");
+        let original = DynamicImage::ImageRgba8(RgbaImage::from_fn(64, 48, |x, y| image::Rgba([x as u8 * 4, y as u8 * 5, 200, 128 + x as u8])));
+
+        ImageProcessor::encode(&original, ImageFormat::Webp, None, &path).unwrap();
+
+        assert_eq!(image::open(&path).unwrap().to_rgba8(), original.to_rgba8());
+        assert!(!path.with_extension("webp.partial").exists());
+    }
+}
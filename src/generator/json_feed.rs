@@ -0,0 +1,74 @@
+use anyhow::Result;
+use serde::Serialize;
+use crate::config::{Config, FeedFormat};
+use crate::content::Page;
+use super::feed::{FeedChannel, FeedGenerator};
+
+/// Top-level JSON Feed 1.1 document
+#[derive(Serialize)]
+struct JsonFeed {
+    version: &'static str,
+    title: String,
+    home_page_url: String,
+    feed_url: String,
+    description: String,
+    language: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    authors: Vec<JsonFeedAuthor>,
+    items: Vec<JsonFeedItem>,
+}
+
+#[derive(Serialize)]
+struct JsonFeedAuthor {
+    name: String,
+}
+
+#[derive(Serialize)]
+struct JsonFeedItem {
+    id: String,
+    url: String,
+    title: String,
+    content_html: String,
+    summary: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date_published: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date_modified: Option<String>,
+}
+
+pub struct JsonFeedGenerator;
+
+impl JsonFeedGenerator {
+    /// Render a JSON Feed 1.1 document for a channel
+    pub fn render(channel: &FeedChannel, config: &Config) -> Result<String> {
+        let feed = JsonFeed {
+            version: "https://jsonfeed.org/version/1.1",
+            title: channel.title.clone(),
+            home_page_url: config.absolute_url("/"),
+            feed_url: channel.feed_url(FeedFormat::Json, config),
+            description: config.description.clone(),
+            language: config.language.clone(),
+            authors: config
+                .author
+                .iter()
+                .map(|name| JsonFeedAuthor { name: name.clone() })
+                .collect(),
+            items: channel.pages.iter().map(|page| Self::build_item(page, config)).collect(),
+        };
+
+        Ok(serde_json::to_string_pretty(&feed)?)
+    }
+
+    fn build_item(page: &Page, config: &Config) -> JsonFeedItem {
+        let url = config.absolute_url(&page.url);
+        JsonFeedItem {
+            id: url.clone(),
+            url,
+            title: page.title.clone(),
+            content_html: FeedGenerator::entry_html(page, config),
+            summary: page.summary.clone(),
+            date_published: page.date.map(|date| date.to_rfc3339()),
+            date_modified: page.updated.map(|date| date.to_rfc3339()),
+        }
+    }
+}
@@ -0,0 +1,302 @@
+use anyhow::Result;
+use glob::glob;
+use std::fs;
+
+/// Elements whose content isn't HTML: whitespace matters in `<pre>` and `<textarea>`,
+/// inline scripts are left exactly as written and inline styles are minified as CSS
+const RAW_TEXT_ELEMENTS: [&str; 4] = ["pre", "textarea", "script", "style"];
+
+/// Elements that don't render surrounding whitespace, so it can be dropped next to them
+const BLOCK_ELEMENTS: &[&str] = &[
+    "!doctype", "address", "article", "aside", "blockquote", "body", "br", "dd", "details", "div", "dl", "dt",
+    "fieldset", "figcaption", "figure", "footer", "form", "h1", "h2", "h3", "h4", "h5", "h6", "head", "header",
+    "hr", "html", "li", "link", "main", "meta", "nav", "noscript", "ol", "p", "pre", "script", "section", "style",
+    "summary", "table", "tbody", "td", "tfoot", "th", "thead", "title", "tr", "ul",
+];
+
+/// Bytes before and after minifying the output directory
+#[derive(Debug, Default)]
+pub struct MinifyStats {
+    pub files: usize,
+    pub before: usize,
+    pub after: usize,
+}
+
+impl MinifyStats {
+    pub fn saved(&self) -> usize {
+        self.before.saturating_sub(self.after)
+    }
+}
+
+pub struct Minifier;
+
+impl Minifier {
+    /// Minify every HTML, CSS and JS file in the output directory in place. Files that are
+    /// already minified (`*.min.css`, `*.min.js`) are skipped.
+    pub fn minify_dir(output_dir: &str) -> Result<MinifyStats> {
+        let mut stats = MinifyStats::default();
+
+        for extension in ["html", "css", "js"] {
+            for entry in glob(&format!("{}/**/*.{}", output_dir, extension))? {
+                let path = entry?;
+                if path.to_string_lossy().ends_with(&format!(".min.{}", extension)) {
+                    continue;
+                }
+
+                let source = fs::read_to_string(&path)?;
+                let minified = match extension {
+                    "html" => Self::html(&source),
+                    "css" => Self::css(&source),
+                    _ => Self::js(&source),
+                };
+
+                stats.files += 1;
+                stats.before += source.len();
+                stats.after += minified.len();
+                fs::write(&path, minified)?;
+            }
+        }
+
+        Ok(stats)
+    }
+
+    /// Drop comments and collapse whitespace between tags. Whitespace next to block-level
+    /// elements is removed, elsewhere it is kept as a single space so inline text flows
+    /// the same.
+    pub fn html(source: &str) -> String {
+        let mut out = String::with_capacity(source.len());
+        let mut text = String::new();
+        let mut previous_block = true;
+        let mut rest = source;
+
+        while let Some(start) = rest.find('<') {
+            text.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            if rest.starts_with("<!--") {
+                let end = rest.find("-->").map_or(rest.len(), |end| end + 3);
+                rest = &rest[end..];
+                continue;
+            }
+
+            let name = Self::tag_name(rest);
+            if name.is_empty() {
+                // A stray `<` in text
+                text.push('<');
+                rest = &rest[1..];
+                continue;
+            }
+
+            let block = BLOCK_ELEMENTS.contains(&name.as_str());
+            out.push_str(&Self::collapse_whitespace(&text, previous_block, block));
+            text.clear();
+
+            let end = Self::tag_end(rest);
+            let opening = !rest.starts_with("</") && !rest[..end].ends_with("/>");
+            out.push_str(&rest[..end]);
+            rest = &rest[end..];
+            previous_block = block;
+
+            if opening && RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+                let close = format!("</{}", name);
+                let content_end = Self::find_ignore_case(rest, &close).unwrap_or(rest.len());
+                // Inline styles, such as inlined critical CSS, are minified like stylesheets
+                if name == "style" {
+                    out.push_str(&Self::css(&rest[..content_end]));
+                } else {
+                    out.push_str(&rest[..content_end]);
+                }
+                rest = &rest[content_end..];
+            }
+        }
+
+        text.push_str(rest);
+        out.push_str(&Self::collapse_whitespace(&text, previous_block, true));
+        out
+    }
+
+    /// Byte offset of the first match of the ASCII `needle` in `haystack`, ignoring case
+    fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+        haystack
+            .as_bytes()
+            .windows(needle.len())
+            .position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+    }
+
+    /// Lowercase name of the opening or closing tag `tag` starts with, `""` when it isn't a tag
+    fn tag_name(tag: &str) -> String {
+        tag[1..]
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric() || *c == '!' || *c == '-')
+            .collect::<String>()
+            .to_ascii_lowercase()
+    }
+
+    /// Byte offset just past the `>` closing the tag, skipping `>` inside quoted attributes
+    fn tag_end(tag: &str) -> usize {
+        let mut quote = None;
+        for (index, c) in tag.char_indices() {
+            match (quote, c) {
+                (None, '"' | '\'') => quote = Some(c),
+                (Some(open), _) if c == open => quote = None,
+                (None, '>') => return index + 1,
+                _ => {}
+            }
+        }
+        tag.len()
+    }
+
+    fn collapse_whitespace(text: &str, trim_start: bool, trim_end: bool) -> String {
+        let mut collapsed = String::with_capacity(text.len());
+        let mut space = false;
+        // Only ASCII whitespace collapses; `&nbsp;` written as U+00A0 must survive
+        for c in text.chars() {
+            if c.is_ascii_whitespace() {
+                space = true;
+                continue;
+            }
+            if space && !(collapsed.is_empty() && trim_start) {
+                collapsed.push(' ');
+            }
+            space = false;
+            collapsed.push(c);
+        }
+        if space && !trim_end && !(collapsed.is_empty() && trim_start) {
+            collapsed.push(' ');
+        }
+        collapsed
+    }
+
+    /// Drop comments and whitespace that doesn't separate tokens. Strings are copied as they
+    /// are, and spaces around `+` and `-` are kept for `calc()`.
+    pub fn css(source: &str) -> String {
+        const NO_SPACE_AFTER: &[char] = &['{', '}', ';', ':', ',', '>', '~', '('];
+        const NO_SPACE_BEFORE: &[char] = &['{', '}', ';', ',', '>', '~', ')', '!'];
+
+        let mut out = String::with_capacity(source.len());
+        let mut space = false;
+        let mut chars = source.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if c.is_ascii_whitespace() {
+                space = true;
+                continue;
+            }
+            if c == '/' && chars.peek() == Some(&'*') {
+                chars.next();
+                let mut previous = ' ';
+                for comment in chars.by_ref() {
+                    if previous == '*' && comment == '/' {
+                        break;
+                    }
+                    previous = comment;
+                }
+                space = true;
+                continue;
+            }
+
+            if space
+                && let Some(last) = out.chars().next_back()
+                && !NO_SPACE_AFTER.contains(&last)
+                && !NO_SPACE_BEFORE.contains(&c)
+            {
+                out.push(' ');
+            }
+            space = false;
+
+            // The last declaration in a block needs no semicolon
+            if c == '}' && out.ends_with(';') {
+                out.pop();
+            }
+            out.push(c);
+
+            if c == '"' || c == '\'' {
+                let mut escaped = false;
+                for string in chars.by_ref() {
+                    out.push(string);
+                    if string == c && !escaped {
+                        break;
+                    }
+                    escaped = string == '\\' && !escaped;
+                }
+            }
+        }
+
+        out
+    }
+
+    /// Conservative JS minification: drop indentation, blank lines and whole-line comments
+    /// but keep every line break, so automatic semicolon insertion behaves the same. Lines
+    /// inside multi-line template literals are kept verbatim.
+    pub fn js(source: &str) -> String {
+        let mut lines = Vec::new();
+        let mut in_template = false;
+        let mut in_comment = false;
+
+        for line in source.lines() {
+            if in_template {
+                lines.push(line.to_string());
+            } else {
+                let trimmed = line.trim();
+                let trimmed = if in_comment {
+                    let Some((_, after)) = trimmed.split_once("*/") else {
+                        continue;
+                    };
+                    in_comment = false;
+                    after.trim_start()
+                } else {
+                    trimmed
+                };
+                if trimmed.starts_with("/*") && !trimmed.contains("*/") {
+                    in_comment = true;
+                    continue;
+                }
+                let whole_line_comment = trimmed.starts_with("//") || (trimmed.starts_with("/*") && trimmed.ends_with("*/"));
+                if trimmed.is_empty() || whole_line_comment {
+                    continue;
+                }
+                lines.push(trimmed.to_string());
+            }
+
+            // An odd number of backticks opens or closes a template literal spanning lines
+            if line.matches('`').count() % 2 == 1 {
+                in_template = !in_template;
+            }
+        }
+
+        let mut minified = lines.join("\n");
+        minified.push('\n');
+        minified
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TestDir;
+
+    #[test]
+    fn keeps_preformatted_text_and_inline_scripts() {
+        let html = "<div>\n  <p>Some   <em>text</em>\n  </p>\n  <PRE>  fn main() {\n      indented();\n  }\n</Pre>\n  <script>\n  if (a < b) {\n    go();\n  }\n  </SCRIPT>\n</div>";
+        assert_eq!(
+            Minifier::html(html),
+            "<div><p>Some <em>text</em></p><PRE>  fn main() {\n      indented();\n  }\n</Pre><script>\n  if (a < b) {\n    go();\n  }\n  </SCRIPT></div>"
+        );
+    }
+
+    #[test]
+    fn reports_bytes_saved() {
+        let dir = TestDir::new("minify");
+        let page = "<!-- generated -->\n<html>\n  <body>\n    <p>Hello</p>\n  </body>\n</html>\n";
+        dir.write("index.html", page);
+        dir.write("assets/site.min.css", "a { color : red ; }");
+
+        let stats = Minifier::minify_dir(&dir.path().to_string_lossy()).unwrap();
+        let minified = dir.read("index.html");
+        assert_eq!(minified, "<html><body><p>Hello</p></body></html>");
+        assert_eq!((stats.files, stats.before, stats.after), (1, page.len(), minified.len()));
+        assert_eq!(stats.saved(), page.len() - minified.len());
+        assert_eq!(dir.read("assets/site.min.css"), "a { color : red ; }");
+    }
+}
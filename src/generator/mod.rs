@@ -1,4 +1,17 @@
 pub mod site_generator;
+pub mod feed;
 pub mod rss;
+pub mod atom;
+pub mod json_feed;
+pub mod sitemap;
+pub mod robots;
+pub mod search;
+pub mod seo;
+pub mod social_card;
+pub mod images;
+pub mod css_pruner;
+pub mod minify;
+pub mod fingerprint;
+pub mod static_files;
 
-pub use site_generator::SiteGenerator;
\ No newline at end of file
+pub use site_generator::SiteGenerator;
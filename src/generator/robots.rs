@@ -0,0 +1,35 @@
+use anyhow::Result;
+use std::fs;
+
+use crate::config::Config;
+
+pub struct RobotsGenerator;
+
+impl RobotsGenerator {
+    /// Write `robots.txt` from the `[robots]` config, pointing crawlers at the sitemap
+    pub fn generate(output_dir: &str, config: &Config) -> Result<()> {
+        let robots = &config.robots;
+
+        let mut content = format!("User-agent: {}\n", robots.user_agent);
+        if robots.disallow.is_empty() {
+            // An empty Disallow explicitly allows everything
+            content.push_str("Disallow:\n");
+        }
+        for path in &robots.disallow {
+            content.push_str(&format!("Disallow: {}\n", path));
+        }
+
+        if let Some(extra) = &robots.extra {
+            content.push('\n');
+            content.push_str(extra.trim_end());
+            content.push('\n');
+        }
+
+        if config.sitemap.enabled {
+            content.push_str(&format!("\nSitemap: {}\n", config.absolute_url("/sitemap.xml")));
+        }
+
+        fs::write(format!("{}/robots.txt", output_dir), content)?;
+        Ok(())
+    }
+}
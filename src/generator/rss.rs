@@ -1,84 +1,163 @@
-use anyhow::Result;
-use rss::{ChannelBuilder, ItemBuilder};
-use std::fs;
-use std::path::Path;
-use crate::content::MarkdownProcessor;
+use rss::extension::atom::{AtomExtensionBuilder, Link};
+use rss::extension::dublincore::DublinCoreExtensionBuilder;
+use rss::extension::itunes::{
+    ITunesCategoryBuilder, ITunesChannelExtension, ITunesChannelExtensionBuilder, ITunesItemExtension,
+    ITunesItemExtensionBuilder, ITunesOwnerBuilder,
+};
+use rss::extension::{Extension, ExtensionBuilder, ExtensionMap};
+use rss::{ChannelBuilder, EnclosureBuilder, GuidBuilder, ImageBuilder, Item, ItemBuilder};
+use std::collections::BTreeMap;
+use crate::config::{Config, FeedFormat};
+use crate::content::podcast::PodcastEpisode;
+use crate::content::Page;
+use super::feed::{FeedChannel, FeedGenerator};
+
+/// Podcasting 2.0 namespace, see https://podcastindex.org/namespace/1.0
+const PODCAST_NAMESPACE: &str = "https://podcastindex.org/namespace/1.0";
 
 pub struct RssGenerator;
 
 impl RssGenerator {
-    /// Generate RSS feed from markdown posts
-    pub fn generate_rss_feed(input_dir: &str, output_dir: &str, posts: &[String]) -> Result<()> {
-        let mut items = Vec::new();
-
-        for post_path in posts {
-            // Skip non-post files (like index.md, pages/*)
-            if !post_path.contains("/posts/") {
-                continue;
-            }
-
-            let content = fs::read_to_string(post_path)?;
-            let html_content = MarkdownProcessor::to_html(&content);
-            
-            // Extract title from the first line if it's a header
-            let title = if content.starts_with("# ") {
-                content.lines().next()
-                    .unwrap_or("")
-                    .trim_start_matches("# ")
-                    .to_string()
-            } else {
-                MarkdownProcessor::extract_title_from_path(post_path)
-            };
-
-            // Extract date from filename (assuming format: YYYY-MM-DD-title.md)
-            let filename = Path::new(post_path)
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("");
-            
-            let date_str = if filename.len() >= 10 && filename.chars().nth(4) == Some('-') {
-                &filename[..10] // Extract YYYY-MM-DD
-            } else {
-                "2025-01-01" // Default date
-            };
-
-            // Create RSS item
-            let link = format!("/{}", post_path
-                .replace(input_dir, "")
-                .trim_start_matches('/')
-                .replace(".md", ".html"));
-
-            let item = ItemBuilder::default()
-                .title(Some(title))
-                .link(Some(link))
-                .description(Some(html_content))
-                .pub_date(Some(format!("{} 00:00:00 +0000", date_str)))
-                .build();
-
-            items.push(item);
+    /// Render an RSS 2.0 feed for a channel. The podcast channel also gets audio
+    /// enclosures plus iTunes and Podcasting 2.0 tags.
+    pub fn render(channel: &FeedChannel, config: &Config) -> String {
+        let items: Vec<Item> = channel
+            .pages
+            .iter()
+            .map(|page| Self::build_item(page, channel.podcast, config))
+            .collect();
+
+        let mut self_link = Link::default();
+        self_link.set_href(channel.feed_url(FeedFormat::Rss, config));
+        self_link.set_rel("self");
+        self_link.set_mime_type(Some(FeedFormat::Rss.mime_type().to_string()));
+
+        // Create RSS channel
+        let mut builder = ChannelBuilder::default();
+        builder
+            .title(channel.title.clone())
+            .link(config.absolute_url("/"))
+            .description(config.description.clone())
+            .language(Some(config.language.clone()))
+            .generator(Some("Dodge SSG".to_string()))
+            .last_build_date(channel.updated().map(|date| date.to_rfc2822()))
+            .atom_ext(Some(AtomExtensionBuilder::default().links(vec![self_link]).build()))
+            .items(items);
+
+        if channel.podcast {
+            let artwork = Self::artwork_url(config);
+            builder
+                .image(artwork.as_ref().map(|url| {
+                    ImageBuilder::default()
+                        .url(url.clone())
+                        .title(channel.title.clone())
+                        .link(config.absolute_url("/"))
+                        .build()
+                }))
+                .itunes_ext(Some(Self::itunes_channel(artwork, config)))
+                .namespaces(BTreeMap::from([("podcast".to_string(), PODCAST_NAMESPACE.to_string())]))
+                .extensions(Self::podcast_extensions(vec![("locked", "no".to_string())]));
         }
 
-        // Sort items by date (newest first)
-        items.sort_by(|a, b| {
-            b.pub_date().unwrap_or("").cmp(a.pub_date().unwrap_or(""))
+        builder.build().to_string()
+    }
+
+    /// Build a feed item with absolute links and a permalink GUID
+    fn build_item(page: &Page, podcast: bool, config: &Config) -> Item {
+        let link = config.absolute_url(&page.url);
+
+        let dublin_core = config.author.as_ref().map(|author| {
+            DublinCoreExtensionBuilder::default()
+                .creators(vec![author.clone()])
+                .build()
         });
 
-        // Create RSS channel
-        let channel = ChannelBuilder::default()
-            .title("My Blog")
-            .link("http://localhost:3000")
-            .description("A blog powered by Dodge SSG")
-            .language(Some("en-us".to_string()))
-            .items(items)
-            .build();
-
-        // Write RSS feed to file
-        let rss_content = channel.to_string();
-        let rss_path = format!("{}/rss.xml", output_dir);
-        fs::write(rss_path, rss_content)?;
-
-        Ok(())
+        let mut builder = ItemBuilder::default();
+        builder
+            .title(Some(page.title.clone()))
+            .link(Some(link.clone()))
+            .guid(Some(GuidBuilder::default().value(link).permalink(true).build()))
+            .description(Some(FeedGenerator::entry_html(page, config)))
+            .pub_date(page.date.map(|date| date.to_rfc2822()))
+            .dublin_core_ext(dublin_core);
+
+        if let Some(episode) = page.episode.as_ref().filter(|_| podcast) {
+            builder
+                .enclosure(Some(
+                    EnclosureBuilder::default()
+                        .url(config.absolute_url(&episode.audio_url))
+                        .length(episode.length.to_string())
+                        .mime_type(episode.mime_type.clone())
+                        .build(),
+                ))
+                .itunes_ext(Some(Self::itunes_item(page, episode)))
+                .extensions(Self::podcast_extensions(
+                    [("season", episode.season), ("episode", episode.episode)]
+                        .into_iter()
+                        .filter_map(|(name, number)| number.map(|number| (name, number.to_string())))
+                        .collect(),
+                ));
+        }
+
+        builder.build()
     }
 
+    /// Show-level iTunes tags: artwork, author, category, explicit flag and owner
+    fn itunes_channel(artwork: Option<String>, config: &Config) -> ITunesChannelExtension {
+        let podcast = &config.podcast;
+
+        let owner = (podcast.owner_name.is_some() || podcast.owner_email.is_some()).then(|| {
+            ITunesOwnerBuilder::default()
+                .name(podcast.owner_name.clone())
+                .email(podcast.owner_email.clone())
+                .build()
+        });
+
+        ITunesChannelExtensionBuilder::default()
+            .author(config.author.clone())
+            .image(artwork)
+            .categories(
+                podcast
+                    .category
+                    .iter()
+                    .map(|category| ITunesCategoryBuilder::default().text(category.clone()).build())
+                    .collect::<Vec<_>>(),
+            )
+            .explicit(Some(podcast.explicit.to_string()))
+            .owner(owner)
+            .summary(Some(config.description.clone()))
+            .r#type(Some("episodic".to_string()))
+            .build()
+    }
 
-}
\ No newline at end of file
+    /// Episode-level iTunes tags
+    fn itunes_item(page: &Page, episode: &PodcastEpisode) -> ITunesItemExtension {
+        ITunesItemExtensionBuilder::default()
+            .duration(episode.duration.clone())
+            .episode(episode.episode.map(|number| number.to_string()))
+            .season(episode.season.map(|number| number.to_string()))
+            .episode_type(Some("full".to_string()))
+            .summary(Some(page.summary.clone()))
+            .build()
+    }
+
+    /// Simple `<podcast:name>value</podcast:name>` elements
+    fn podcast_extensions(elements: Vec<(&str, String)>) -> ExtensionMap {
+        let mut tags: BTreeMap<String, Vec<Extension>> = BTreeMap::new();
+        for (name, value) in elements {
+            let qualified = format!("podcast:{}", name);
+            tags.entry(qualified.clone())
+                .or_default()
+                .push(ExtensionBuilder::default().name(qualified).value(Some(value)).build());
+        }
+        BTreeMap::from([("podcast".to_string(), tags)])
+    }
+
+    fn artwork_url(config: &Config) -> Option<String> {
+        config
+            .podcast
+            .image
+            .as_ref()
+            .map(|image| config.absolute_url(image))
+    }
+}
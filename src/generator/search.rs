@@ -0,0 +1,72 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+
+use crate::config::Config;
+use crate::content::{ContentProcessor, Page};
+
+/// Site-relative path of the search index
+pub const SEARCH_INDEX_PATH: &str = "/search/index.json";
+
+/// Client-side widget that queries the index
+const SEARCH_SCRIPT: &str = include_str!("../../themes/search.js");
+
+/// One searchable page. Body text is reduced to its distinct lowercase terms to keep the index small.
+#[derive(Serialize)]
+struct SearchEntry<'a> {
+    title: &'a str,
+    url: &'a str,
+    summary: &'a str,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    headings: Vec<&'a str>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    tags: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date: Option<String>,
+    terms: String,
+}
+
+pub struct SearchIndexGenerator;
+
+impl SearchIndexGenerator {
+    /// Write the search index for every page without `search: false`, and the widget script
+    pub fn generate(output_dir: &str, pages: &[Page], config: &Config) -> Result<usize> {
+        let entries: Vec<SearchEntry> = pages
+            .iter()
+            .filter(|page| page.in_search)
+            .map(|page| SearchEntry {
+                title: &page.title,
+                url: &page.url,
+                summary: &page.summary,
+                headings: page.headings.iter().map(|heading| heading.title.as_str()).collect(),
+                tags: &page.tags,
+                date: page.date.map(|date| date.format("%Y-%m-%d").to_string()),
+                terms: Self::terms(&page.text, config.search.max_terms),
+            })
+            .collect();
+
+        let index_path = format!("{}{}", output_dir, SEARCH_INDEX_PATH);
+        ContentProcessor::ensure_output_dir(&index_path)?;
+        fs::write(&index_path, serde_json::to_string(&entries)?)?;
+
+        let script_path = format!("{}/assets/search.js", output_dir);
+        ContentProcessor::ensure_output_dir(&script_path)?;
+        fs::write(&script_path, SEARCH_SCRIPT)?;
+
+        Ok(entries.len())
+    }
+
+    /// Distinct lowercase terms in order of first appearance, skipping single characters
+    fn terms(text: &str, limit: usize) -> String {
+        let mut seen = HashSet::new();
+        let terms: Vec<String> = text
+            .split(|ch: char| !ch.is_alphanumeric())
+            .filter(|word| word.chars().count() > 1)
+            .map(str::to_lowercase)
+            .filter(|word| seen.insert(word.clone()))
+            .take(if limit == 0 { usize::MAX } else { limit })
+            .collect();
+        terms.join(" ")
+    }
+}
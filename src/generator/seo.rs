@@ -0,0 +1,121 @@
+use chrono::SecondsFormat;
+use serde_json::{json, Value};
+
+use crate::config::Config;
+use crate::content::Page;
+use crate::utils::HtmlUtils;
+
+pub struct SeoTags;
+
+impl SeoTags {
+    /// Description, canonical link, Open Graph and Twitter card tags for a page's `<head>`.
+    /// Dated pages are posts and also get article times and JSON-LD `BlogPosting` data.
+    pub fn render(page: &Page, config: &Config) -> String {
+        let url = config.absolute_url(&page.url);
+        let image = Self::image_url(page, config);
+        let is_post = page.date.is_some();
+
+        let mut tags = vec![
+            Self::meta("name", "description", &page.summary),
+            format!("<link rel=\"canonical\" href=\"{}\">", HtmlUtils::escape(&url)),
+            Self::meta("property", "og:type", if is_post { "article" } else { "website" }),
+            Self::meta("property", "og:title", &page.title),
+            Self::meta("property", "og:description", &page.summary),
+            Self::meta("property", "og:url", &url),
+            Self::meta("property", "og:site_name", &config.blog_title),
+            Self::meta("property", "og:locale", &config.language.replace('-', "_")),
+        ];
+
+        if let Some(image) = &image {
+            tags.push(Self::meta("property", "og:image", image));
+            tags.push(Self::meta("property", "og:image:alt", &page.title));
+        }
+
+        if let Some(date) = page.date {
+            tags.push(Self::meta("property", "article:published_time", &date.to_rfc3339_opts(SecondsFormat::Secs, true)));
+        }
+        if let Some(updated) = page.updated {
+            tags.push(Self::meta("property", "article:modified_time", &updated.to_rfc3339_opts(SecondsFormat::Secs, true)));
+        }
+        if is_post {
+            if let Some(author) = &config.author {
+                tags.push(Self::meta("property", "article:author", author));
+            }
+            for tag in &page.tags {
+                tags.push(Self::meta("property", "article:tag", tag));
+            }
+        }
+
+        let card = if image.is_some() { "summary_large_image" } else { "summary" };
+        tags.push(Self::meta("name", "twitter:card", card));
+        tags.push(Self::meta("name", "twitter:title", &page.title));
+        tags.push(Self::meta("name", "twitter:description", &page.summary));
+        if let Some(image) = &image {
+            tags.push(Self::meta("name", "twitter:image", image));
+        }
+        if let Some(site) = &config.seo.twitter_site {
+            tags.push(Self::meta("name", "twitter:site", site));
+        }
+        if let Some(creator) = &config.seo.twitter_creator {
+            tags.push(Self::meta("name", "twitter:creator", creator));
+        }
+
+        if is_post {
+            tags.push(Self::json_ld(page, config, &url, image.as_deref()));
+        }
+
+        tags.iter().map(|tag| format!("\n    {}", tag)).collect()
+    }
+
+    /// Absolute share image: the page's own `image`, its generated social card, else the site default
+    fn image_url(page: &Page, config: &Config) -> Option<String> {
+        page.image
+            .as_ref()
+            .or(page.social_card.as_ref())
+            .or(config.seo.image.as_ref())
+            .map(|image| config.absolute_url(image))
+    }
+
+    fn meta(attribute: &str, key: &str, content: &str) -> String {
+        format!(
+            "<meta {}=\"{}\" content=\"{}\">",
+            attribute,
+            key,
+            HtmlUtils::escape(content)
+        )
+    }
+
+    /// schema.org `BlogPosting` structured data
+    fn json_ld(page: &Page, config: &Config, url: &str, image: Option<&str>) -> String {
+        let format_date = |date: chrono::DateTime<chrono::FixedOffset>| date.to_rfc3339_opts(SecondsFormat::Secs, true);
+
+        let mut posting = json!({
+            "@context": "https://schema.org",
+            "@type": "BlogPosting",
+            "headline": page.title,
+            "description": page.summary,
+            "url": url,
+            "mainEntityOfPage": { "@type": "WebPage", "@id": url },
+            "inLanguage": config.language,
+            "wordCount": page.word_count,
+            "publisher": { "@type": "Organization", "name": config.blog_title, "url": config.absolute_url("/") },
+        });
+
+        let fields = [
+            ("datePublished", page.date.map(format_date).map(Value::from)),
+            ("dateModified", page.updated.or(page.date).map(format_date).map(Value::from)),
+            ("image", image.map(Value::from)),
+            ("author", config.author.as_ref().map(|author| json!({ "@type": "Person", "name": author }))),
+            ("keywords", (!page.tags.is_empty()).then(|| Value::from(page.tags.join(", ")))),
+        ];
+        for (key, value) in fields {
+            if let Some(value) = value {
+                posting[key] = value;
+            }
+        }
+
+        // Keep a "</script>" inside any string from closing the block early
+        let json = posting.to_string().replace("</", "<\\/");
+        format!("<script type=\"application/ld+json\">{}</script>", json)
+    }
+}
@@ -2,14 +2,40 @@ use anyhow::Result;
 use std::fs;
 use std::path::Path;
 
-use crate::theme::{Theme, ThemeAssets};
-use crate::utils::AsciiArtGenerator;
-use crate::content::{ContentProcessor, MarkdownProcessor};
-use super::rss::RssGenerator;
+use crate::theme::toggle::TOGGLE_SCRIPT_URL;
+use crate::theme::{Layout, SiteStyles, Theme, ThemeAssets};
+use crate::utils::{AsciiArtGenerator, HtmlUtils};
+use crate::config::Config;
+use crate::content::links::LinkValidator;
+use crate::content::{ContentProcessor, Page, PostList, SectionDefaults, TableOfContents};
+use super::feed::FeedGenerator;
+use super::robots::RobotsGenerator;
+use super::search::{SearchIndexGenerator, SEARCH_INDEX_PATH};
+use super::seo::SeoTags;
+use super::social_card::SocialCardGenerator;
+use super::images::ImageProcessor;
+use super::css_pruner::CssPruner;
+use super::minify::Minifier;
+use super::fingerprint::{AssetFingerprinter, MANIFEST_FILE};
+use super::static_files::StaticFiles;
+use super::sitemap::SitemapGenerator;
+
+/// Print a build progress line to stdout, or to stderr when stdout carries other output
+macro_rules! progress {
+    ($generator:expr, $($arg:tt)*) => {
+        if $generator.progress_to_stderr {
+            eprintln!($($arg)*)
+        } else {
+            println!($($arg)*)
+        }
+    };
+}
 
 pub struct SiteGenerator {
     pub input_dir: String,
     pub output_dir: String,
+    /// Send progress messages to stderr, e.g. when `dodge check` prints a report to stdout
+    pub progress_to_stderr: bool,
 }
 
 impl SiteGenerator {
@@ -17,173 +43,406 @@ impl SiteGenerator {
         Self {
             input_dir,
             output_dir,
+            progress_to_stderr: false,
         }
     }
 
     /// Generate the entire site
-    pub fn build(&self) -> Result<()> {
-        self.build_with_theme(&Theme::Vercel)
-    }
-
-    pub fn build_with_config(&self, blog_title: &str, theme: &Theme) -> Result<()> {
-        println!("🚀 Building site...");
-        println!("📁 Input: {}", self.input_dir);
-        println!("📁 Output: {}", self.output_dir);
-        println!("🎨 Theme: {}", theme);
-        println!("📝 Blog Title: {}", blog_title);
+    pub fn build_with_config(&self, config: &Config, theme: &Theme) -> Result<()> {
+        progress!(self, "🚀 Building site...");
+        progress!(self, "📁 Input: {}", self.input_dir);
+        progress!(self, "📁 Output: {}", self.output_dir);
+        progress!(self, "🎨 Theme: {}", theme);
+        progress!(self, "📝 Blog Title: {}", config.blog_title);
 
         // Create output directory
         fs::create_dir_all(&self.output_dir)?;
 
-        // Copy theme assets (CSS, etc.)
-        ThemeAssets::copy_theme_assets(theme, &self.output_dir)?;
-
-        // Collect and process all markdown files
+        // Collect and process all markdown files, with defaults from their sections' `_index.md`
         let posts = ContentProcessor::collect_posts(&self.input_dir)?;
+        let sections = SectionDefaults::load(&self.input_dir)?;
+
+        let mut pages = posts
+            .iter()
+            .map(|post| Page::load(post, &self.input_dir, &sections.for_page(post, &self.input_dir), config))
+            .collect::<Result<Vec<_>>>()?;
+        for page in &pages {
+            for warning in &page.warnings {
+                progress!(self, "⚠️  {} in {}", warning, page.source_path);
+            }
+        }
+
+        // `[[posts]]` lists every dated page, so it is filled in once all are loaded
+        PostList::expand(&mut pages);
+
+        // Copy theme assets (CSS, etc.) for every theme in use and compile Sass before
+        // minifying and fingerprinting see them
+        let page_themes: Vec<Theme> = pages.iter().filter_map(|page| page.theme).collect();
+        let styles = ThemeAssets::copy_theme_assets(theme, &page_themes, config, &self.output_dir)?;
+        let sass_count = styles.stylesheets.iter().filter(|stylesheet| stylesheet.theme.is_none()).count();
+        if sass_count > 0 {
+            progress!(self, "🎨 Compiled {} Sass stylesheets", sass_count);
+        }
+        if let Some(toggle) = &styles.toggle {
+            let labels: Vec<&str> = toggle.variants.iter().map(|variant| variant.label.as_str()).collect();
+            progress!(self, "🌓 Theme toggle: {}", labels.join(" / "));
+        }
+
+        // Copy the static directory, then files living next to the markdown (page bundles)
+        let static_count = StaticFiles::copy_dir(&config.build.static_dir, &self.output_dir)?;
+        let asset_count = StaticFiles::copy_content_assets(&self.input_dir, &self.output_dir)?;
+        progress!(self, "📦 Copied {} static files and {} content assets", static_count, asset_count);
+
+        // Render social cards for pages without their own share image
+        if config.social_cards.enabled {
+            let cards = SocialCardGenerator::new()?;
+            let (mut rendered, mut cached) = (0, 0);
+            for page in pages.iter_mut().filter(|page| page.image.is_none()) {
+                let (url, from_cache) = cards.generate(page, &page.theme.unwrap_or(*theme), config, &self.output_dir)?;
+                page.social_card = Some(url);
+                if from_cache { cached += 1 } else { rendered += 1 }
+            }
+            progress!(self, "🖼️  Social cards: {} rendered, {} cached", rendered, cached);
+        }
+
+        // Encode responsive image variants referenced by the content
+        if config.images.enabled {
+            let (encoded, cached) = ImageProcessor::process(&pages, config, &self.output_dir)?;
+            if encoded + cached > 0 {
+                progress!(self, "🖼️  Images: {} variants encoded, {} cached", encoded, cached);
+            }
+        }
 
         let mut generated_count = 0;
-        for post in &posts {
-            self.generate_page_with_config(post, theme, blog_title)?;
+        for page in &pages {
+            self.generate_page(page, theme, &styles, config)?;
             generated_count += 1;
         }
 
-        // Generate RSS feed
-        RssGenerator::generate_rss_feed(&self.input_dir, &self.output_dir, &posts)?;
+        // Generate feeds (RSS, Atom, JSON Feed)
+        let feeds = FeedGenerator::generate_feeds(&self.output_dir, &pages, config)?;
 
-        println!("✅ Generated {} pages successfully!", generated_count);
-        println!("📡 Generated RSS feed: /rss.xml");
-        Ok(())
-    }
-
-    /// Generate the entire site with a specific theme (deprecated - use build() instead)
-    pub fn build_with_theme(&self, theme: &Theme) -> Result<()> {
-        println!("🚀 Building site...");
-        println!("📁 Input: {}", self.input_dir);
-        println!("📁 Output: {}", self.output_dir);
-        println!("🎨 Theme: {}", theme);
+        progress!(self, "✅ Generated {} pages successfully!", generated_count);
+        for feed in &feeds {
+            progress!(self, "📡 Generated feed: {}", feed);
+        }
 
-        // Create output directory
-        fs::create_dir_all(&self.output_dir)?;
+        // Generate the search index and page
+        if config.search.enabled {
+            let indexed = SearchIndexGenerator::generate(&self.output_dir, &pages, config)?;
+            self.generate_search_page(theme, &styles, config)?;
+            progress!(self, "🔍 Indexed {} pages for search", indexed);
+        }
 
-        // Copy theme assets (CSS, etc.)
-        ThemeAssets::copy_theme_assets(theme, &self.output_dir)?;
+        // Generate sitemap.xml and robots.txt
+        if config.sitemap.enabled {
+            for sitemap in SitemapGenerator::generate(&self.output_dir, &pages, config)? {
+                progress!(self, "🗺️  Generated sitemap: {}", sitemap);
+            }
+        }
+        if config.robots.enabled {
+            RobotsGenerator::generate(&self.output_dir, config)?;
+            progress!(self, "🤖 Generated robots.txt");
+        }
 
-        // Collect and process all markdown files
-        let posts = ContentProcessor::collect_posts(&self.input_dir)?;
+        // Links may point at any generated file, so they are checked once everything is written
+        LinkValidator::validate(&pages, config, &self.output_dir)?;
 
-        let mut generated_count = 0;
-        for post in &posts {
-            self.generate_page_with_theme(post, theme)?;
-            generated_count += 1;
+        // Prune stylesheets once every page exists to scan
+        if config.css.remove_unused || config.css.inline_critical {
+            let mut css_config = config.css.clone();
+            // Inlining one theme's rules would keep styling pages after switching to another
+            if css_config.inline_critical && styles.toggle.as_ref().is_some_and(|toggle| toggle.switches_themes()) {
+                eprintln!("⚠️  Critical CSS inlining is skipped when readers can switch between themes");
+                css_config.inline_critical = false;
+            }
+            let stats = CssPruner::run(&self.output_dir, &css_config)?;
+            if config.css.remove_unused {
+                progress!(
+                    self,
+                    "✂️  Removed {} unused CSS rules: {} → {} bytes",
+                    stats.rules_removed, stats.before, stats.after
+                );
+            }
+            if css_config.inline_critical {
+                progress!(self, "⚡ Inlined critical CSS into {} pages", stats.pages_inlined);
+            }
         }
 
-        // Generate RSS feed
-        RssGenerator::generate_rss_feed(&self.input_dir, &self.output_dir, &posts)?;
+        // Minify last so every generated and copied file is covered
+        if config.build.minify {
+            let stats = Minifier::minify_dir(&self.output_dir)?;
+            let saved = stats.saved();
+            let percent = if stats.before == 0 { 0.0 } else { saved as f64 * 100.0 / stats.before as f64 };
+            progress!(
+                self,
+                "🗜️  Minified {} files: {} → {} bytes (saved {} bytes, {:.1}%)",
+                stats.files, stats.before, stats.after, saved, percent
+            );
+        }
 
-        println!("✅ Generated {} pages successfully!", generated_count);
-        println!("📡 Generated RSS feed: /rss.xml");
+        // Fingerprint after minifying so hashes match the bytes that are served
+        if config.build.fingerprint {
+            let variants: Vec<&str> = pages.iter().flat_map(|page| &page.images).map(|variant| variant.url.as_str()).collect();
+            let count = AssetFingerprinter::fingerprint(&self.output_dir, &variants)?;
+            progress!(self, "🔖 Fingerprinted {} assets, see /{}", count, MANIFEST_FILE);
+        }
         Ok(())
     }
 
-
-
     /// Clean the output directory
     pub fn clean(&self) -> Result<()> {
         if Path::new(&self.output_dir).exists() {
             fs::remove_dir_all(&self.output_dir)?;
-            println!("🧹 Cleaned output directory: {}", self.output_dir);
+            progress!(self, "🧹 Cleaned output directory: {}", self.output_dir);
         }
         Ok(())
     }
 
-    /// Generate a single page from markdown with theme support
-    fn generate_page_with_theme(&self, input_path: &str, theme: &Theme) -> Result<()> {
-        let content = fs::read_to_string(input_path)?;
-        let html_content = MarkdownProcessor::to_html(&content);
-
-        // Wrap content in HTML template
-        let full_html = self.wrap_with_template(&html_content, theme, input_path, &content, "My Blog");
+    /// Generate a single page from markdown with config support
+    fn generate_page(&self, page: &Page, theme: &Theme, styles: &SiteStyles, config: &Config) -> Result<()> {
+        // Wrap content in HTML template, in the page's own theme when it sets one
+        let full_html = self.wrap_with_template(page, &page.theme.unwrap_or(*theme), styles, config);
 
         // Create output path
-        let output_path = ContentProcessor::get_output_path(input_path, &self.input_dir, &self.output_dir);
+        let output_path = ContentProcessor::get_output_path(&page.source_path, &self.input_dir, &self.output_dir);
 
         // Ensure directory exists
         ContentProcessor::ensure_output_dir(&output_path)?;
 
         fs::write(&output_path, full_html)?;
-        println!("📄 Generated: {} -> {}", input_path, output_path);
+        progress!(self, "📄 Generated: {} -> {}", page.source_path, output_path);
         Ok(())
     }
 
-    /// Generate a single page from markdown with config support
-    fn generate_page_with_config(&self, input_path: &str, theme: &Theme, blog_title: &str) -> Result<()> {
-        let content = fs::read_to_string(input_path)?;
-        let html_content = MarkdownProcessor::to_html(&content);
-
-        // Wrap content in HTML template
-        let full_html = self.wrap_with_template(&html_content, theme, input_path, &content, blog_title);
-
-        // Create output path
-        let output_path = ContentProcessor::get_output_path(input_path, &self.input_dir, &self.output_dir);
+    /// Generate `/search/`, which loads the search index and shows results as you type
+    fn generate_search_page(&self, theme: &Theme, styles: &SiteStyles, config: &Config) -> Result<()> {
+        let content = format!(
+            r#"<h1>Search</h1>
+<div class="search" data-search-index="{}">
+    <form class="search-form" role="search">
+        <input type="search" name="q" placeholder="Search posts…" aria-label="Search" autocomplete="off" autofocus>
+    </form>
+    <p class="search-status" aria-live="polite"></p>
+    <ol class="search-results"></ol>
+    <noscript><p>Search requires JavaScript.</p></noscript>
+</div>
+<script src="/assets/search.js" defer></script>
+"#,
+            SEARCH_INDEX_PATH
+        );
+
+        let page = Page {
+            url: "/search/".to_string(),
+            title: "Search".to_string(),
+            summary: format!("Search {}", config.blog_title),
+            content,
+            ..Page::default()
+        };
 
-        // Ensure directory exists
+        let output_path = format!("{}/search/index.html", self.output_dir);
         ContentProcessor::ensure_output_dir(&output_path)?;
-
-        fs::write(&output_path, full_html)?;
-        println!("📄 Generated: {} -> {}", input_path, output_path);
+        fs::write(&output_path, self.wrap_with_template(&page, theme, styles, config))?;
         Ok(())
     }
 
+    /// Search box partial placed in every theme's header
+    fn search_box(theme: &Theme) -> &'static str {
+        match theme {
+            Theme::Hacker => {
+                r#"<form class="search-box" action="/search/" method="get" role="search"><label for="search-box-input">$ grep</label><input id="search-box-input" type="search" name="q" placeholder="pattern" aria-label="Search"></form>"#
+            }
+            Theme::Vercel => {
+                r#"<form class="search-box" action="/search/" method="get" role="search"><input type="search" name="q" placeholder="Search…" aria-label="Search"></form>"#
+            }
+        }
+    }
+
     /// Wrap content in HTML template
-    fn wrap_with_template(&self, content: &str, theme: &Theme, input_path: &str, markdown_content: &str, blog_title: &str) -> String {
-        let page_title = MarkdownProcessor::extract_title(markdown_content, input_path);
-        
+    fn wrap_with_template(&self, page: &Page, theme: &Theme, styles: &SiteStyles, config: &Config) -> String {
+        let blog_title = &config.blog_title;
+        let page_title = &page.title;
+        let content = if page.show_toc {
+            format!("{}{}", TableOfContents::to_html(&page.toc), page.content)
+        } else {
+            page.content.clone()
+        };
+
+        // Dated pages are posts and get a metadata line in their header
+        let post_meta = if config.reading.show && page.date.is_some() {
+            format!(
+                r#"<div class="post-meta"><span class="reading-time">{} min read</span><span class="word-count">{} words</span></div>"#,
+                page.reading_time, page.word_count
+            )
+        } else {
+            String::new()
+        };
+
+        // Feed auto-discovery links for the site and the page's own section
+        let mut feed_dirs = vec![(String::new(), blog_title.to_string(), config.feed.formats.clone())];
+        let is_podcast = config.podcast.section.as_ref() == Some(&page.section);
+        if !page.section.is_empty() && (is_podcast || config.feed.sections.contains(&page.section)) {
+            feed_dirs.push((
+                format!("/{}", page.section),
+                format!("{} - {}", blog_title, page.section),
+                FeedGenerator::channel_formats(is_podcast, config),
+            ));
+        }
+        let feed_links: String = feed_dirs
+            .iter()
+            .flat_map(|(dir, title, formats)| formats.iter().map(move |format| (dir, title, format)))
+            .map(|(dir, title, format)| {
+                format!(
+                    "\n    <link rel=\"alternate\" type=\"{}\" title=\"{}\" href=\"{}/{}\">",
+                    format.mime_type(),
+                    HtmlUtils::escape(title),
+                    dir,
+                    format.file_name()
+                )
+            })
+            .collect();
+
+        // Pages load their theme's stylesheet. With a toggle between themes, the other themes'
+        // stylesheets are linked too but start disabled.
+        let toggle = styles.toggle_for(theme);
+        let toggle_themes = toggle.filter(|toggle| toggle.switches_themes()).map(|toggle| toggle.themes());
+        let stylesheet_links: String = styles
+            .stylesheets
+            .iter()
+            .filter_map(|stylesheet| match (stylesheet.theme, &toggle_themes) {
+                (Some(other), Some(themes)) if themes.contains(&other) => Some(format!(
+                    "\n    <link rel=\"stylesheet\" href=\"{}\" data-theme=\"{}\"{}>",
+                    HtmlUtils::escape(&stylesheet.href),
+                    other,
+                    if other == *theme { "" } else { " disabled" }
+                )),
+                (Some(other), _) if other != *theme => None,
+                _ => Some(format!("\n    <link rel=\"stylesheet\" href=\"{}\">", HtmlUtils::escape(&stylesheet.href))),
+            })
+            .collect();
+
         let theme_class = match theme {
             Theme::Vercel => "theme-vercel",
             Theme::Hacker => "theme-hacker",
         };
 
-        // Theme-specific header and content structure
-        let (header_html, content_html) = match theme {
-            Theme::Hacker => {
-                let ascii_art = AsciiArtGenerator::generate_ascii_art(blog_title);
-                let header = format!(
-                    r#"<div class="ascii-header-container"><div class="ascii-header">{}<br><br>>>> {} <<<"#, 
-                    ascii_art, page_title
-                );
-                let header_complete = format!("{}</div></div>", header);
-                (header_complete, content.to_string())
-            },
-            Theme::Vercel => {
-                let header = format!(
-                    r#"<header class="site-header">
-                        <h1 class="site-title">{}</h1>
-                    </header>"#,
-                    blog_title
-                );
-                (header, content.to_string())
-            }
+        // The toggle script runs before the page paints to apply the reader's variant
+        let (html_attributes, toggle_script, toggle_button) = match toggle.and_then(|toggle| Some((toggle, toggle.default_variant(theme)?))) {
+            Some((toggle, variant)) => (
+                format!(
+                    " data-color-scheme=\"{}\" data-theme-variants=\"{}\"",
+                    HtmlUtils::escape(&variant.scheme),
+                    HtmlUtils::escape(&toggle.to_json())
+                ),
+                format!("\n    <script src=\"{}\"></script>", TOGGLE_SCRIPT_URL),
+                "\n    <button type=\"button\" class=\"theme-toggle\" hidden></button>",
+            ),
+            None => (String::new(), String::new(), ""),
+        };
+
+        // Every theme the reader can switch to gets its header; each theme's stylesheet hides the others'
+        let header_html = match &toggle_themes {
+            Some(themes) => themes
+                .iter()
+                .map(|header_theme| Self::header(header_theme, page, &post_meta, config, true))
+                .collect::<Vec<_>>()
+                .join("\n    "),
+            None => Self::header(theme, page, &post_meta, config, false),
         };
 
         format!(
             r#"<!DOCTYPE html>
-<html lang="en" class="{}">
+<html lang="{}" class="{}"{}>
 <head>
     <meta charset="UTF-8">
     <meta name="viewport" content="width=device-width, initial-scale=1.0">
-    <title>{}</title>
-    <link rel="stylesheet" href="/assets/style.css">
+    <title>{}</title>{}{}{}{}
 </head>
-<body>
+<body class="layout-{}">{}
     {}
     <main class="container">
         {}
     </main>
 </body>
 </html>"#,
-            theme_class, page_title, header_html, content_html
+            HtmlUtils::escape(&config.language), theme_class, html_attributes, HtmlUtils::escape(page_title), SeoTags::render(page, config), stylesheet_links, toggle_script, feed_links, page.layout, toggle_button, header_html, content
         )
     }
 
+    /// Theme-specific header for the page's layout, marked as belonging to its theme when
+    /// several are in use
+    fn header(theme: &Theme, page: &Page, post_meta: &str, config: &Config, theme_only: bool) -> String {
+        let blog_title = &config.blog_title;
+        let search_box = if config.search.enabled { Self::search_box(theme) } else { "" };
+        let only = if theme_only { format!(" data-theme-only=\"{}\"", theme) } else { String::new() };
+        let description = HtmlUtils::escape(&config.description);
 
-}
\ No newline at end of file
+        match (theme, page.layout) {
+            (Theme::Hacker, Layout::Page) => {
+                let ascii_art = AsciiArtGenerator::generate_ascii_art(blog_title);
+                let header = format!(
+                    r#"<div class="ascii-header-container"{}><div class="ascii-header">{}<br><br>>>> {} <<<"#, 
+                    only, ascii_art, page.title
+                );
+                format!("{}{}</div>{}</div>", header, post_meta, search_box)
+            },
+            (Theme::Hacker, Layout::Landing) => {
+                let ascii_art = AsciiArtGenerator::generate_ascii_art(blog_title);
+                format!(
+                    r#"<div class="ascii-header-container site-hero"{}><div class="ascii-header">{}</div><p class="site-description">{}</p>{}</div>"#,
+                    only, ascii_art, description, search_box
+                )
+            },
+            (Theme::Vercel, Layout::Page) => {
+                format!(
+                    r#"<header class="site-header"{}>
+                        <h1 class="site-title">{}</h1>
+                        {}
+                        {}
+                    </header>"#,
+                    only, blog_title, post_meta, search_box
+                )
+            }
+            (Theme::Vercel, Layout::Landing) => {
+                format!(
+                    r#"<header class="site-header site-hero"{}>
+                        <h1 class="site-title">{}</h1>
+                        <p class="site-description">{}</p>
+                        {}
+                    </header>"#,
+                    only, blog_title, description, search_box
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TestDir;
+
+    #[test]
+    fn site_options_only_need_to_exist_in_the_site_theme() {
+        let dir = TestDir::new("mixed-themes");
+        dir.write("content/posts/plain.md", "# Plain");
+        dir.write("content/posts/retro.md", "---\ntitle: Retro\ntheme: hacker\n---\n\n# Retro");
+
+        let mut config = Config { theme: "vercel".to_string(), ..Config::default() };
+        config.build.static_dir = dir.join("static");
+        config.build.sass_dir = dir.join("sass");
+        config.build.theme_dir = dir.join("themes");
+        config.images.cache_dir = dir.join("cache/images");
+        config.social_cards.cache_dir = dir.join("cache/social-cards");
+        config.theme_toggle.variants = vec!["vercel".to_string(), "hacker".to_string()];
+        config.theme_options.insert("accent_hover".to_string(), toml::Value::String("#ff0066".to_string()));
+
+        SiteGenerator::new(dir.join("content"), dir.join("public")).build_with_config(&config, &Theme::Vercel).unwrap();
+        assert!(dir.read("public/assets/style.css").contains("--accent-hover: #ff0066;"));
+        assert!(!dir.read("public/assets/theme-hacker.css").contains("#ff0066"));
+
+        config.theme = "hacker".to_string();
+        let error = SiteGenerator::new(dir.join("content"), dir.join("public")).build_with_config(&config, &Theme::Hacker).unwrap_err();
+        assert!(error.to_string().contains("Unknown theme option 'accent_hover' for theme hacker"), "{}", error);
+    }
+}
@@ -0,0 +1,89 @@
+use anyhow::Result;
+use chrono::{DateTime, FixedOffset, SecondsFormat, Utc};
+use std::fs;
+
+use crate::config::Config;
+use crate::content::Page;
+use crate::utils::HtmlUtils;
+
+/// Most URLs a single sitemap file may list, per the sitemaps.org protocol
+const MAX_URLS_PER_SITEMAP: usize = 50_000;
+
+const SITEMAP_NAMESPACE: &str = "http://www.sitemaps.org/schemas/sitemap/0.9";
+
+pub struct SitemapGenerator;
+
+impl SitemapGenerator {
+    /// Write `sitemap.xml` for every page that isn't excluded with `sitemap: false`.
+    /// Past 50,000 URLs the pages are split into `sitemap-N.xml` files and
+    /// `sitemap.xml` becomes a sitemap index. Returns the site-relative paths written.
+    pub fn generate(output_dir: &str, pages: &[Page], config: &Config) -> Result<Vec<String>> {
+        let mut entries: Vec<(String, Option<DateTime<FixedOffset>>)> = pages
+            .iter()
+            .filter(|page| page.in_sitemap)
+            .map(|page| (config.absolute_url(&page.url), Self::last_modified(page)))
+            .collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+        if entries.len() <= MAX_URLS_PER_SITEMAP {
+            fs::write(format!("{}/sitemap.xml", output_dir), Self::urlset(&entries))?;
+            return Ok(vec!["/sitemap.xml".to_string()]);
+        }
+
+        let mut written = Vec::new();
+        let mut index = Vec::new();
+        for (number, chunk) in entries.chunks(MAX_URLS_PER_SITEMAP).enumerate() {
+            let path = format!("/sitemap-{}.xml", number + 1);
+            fs::write(format!("{}{}", output_dir, path), Self::urlset(chunk))?;
+            let newest = chunk.iter().filter_map(|(_, lastmod)| *lastmod).max();
+            index.push((config.absolute_url(&path), newest));
+            written.push(path);
+        }
+
+        fs::write(format!("{}/sitemap.xml", output_dir), Self::sitemap_index(&index))?;
+        written.insert(0, "/sitemap.xml".to_string());
+        Ok(written)
+    }
+
+    /// `updated` or `date` from front matter, falling back to the source file's modification time
+    fn last_modified(page: &Page) -> Option<DateTime<FixedOffset>> {
+        page.updated.or(page.date).or_else(|| {
+            let modified = fs::metadata(&page.source_path).ok()?.modified().ok()?;
+            Some(DateTime::<Utc>::from(modified).fixed_offset())
+        })
+    }
+
+    fn urlset(entries: &[(String, Option<DateTime<FixedOffset>>)]) -> String {
+        let urls: String = entries
+            .iter()
+            .map(|(url, lastmod)| format!("  <url>\n{}  </url>\n", Self::location(url, *lastmod)))
+            .collect();
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"{}\">\n{}</urlset>\n",
+            SITEMAP_NAMESPACE, urls
+        )
+    }
+
+    fn sitemap_index(sitemaps: &[(String, Option<DateTime<FixedOffset>>)]) -> String {
+        let entries: String = sitemaps
+            .iter()
+            .map(|(url, lastmod)| format!("  <sitemap>\n{}  </sitemap>\n", Self::location(url, *lastmod)))
+            .collect();
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<sitemapindex xmlns=\"{}\">\n{}</sitemapindex>\n",
+            SITEMAP_NAMESPACE, entries
+        )
+    }
+
+    /// `<loc>` and optional `<lastmod>` in W3C datetime format
+    fn location(url: &str, lastmod: Option<DateTime<FixedOffset>>) -> String {
+        let mut xml = format!("    <loc>{}</loc>\n", HtmlUtils::escape(url));
+        if let Some(lastmod) = lastmod {
+            xml.push_str(&format!(
+                "    <lastmod>{}</lastmod>\n",
+                lastmod.to_rfc3339_opts(SecondsFormat::Secs, true)
+            ));
+        }
+        xml
+    }
+}
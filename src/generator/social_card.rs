@@ -0,0 +1,267 @@
+use ab_glyph::{point, Font, FontRef, PxScale, ScaleFont};
+use anyhow::{anyhow, Result};
+use image::{Rgba, RgbaImage};
+use std::fs;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::content::{ContentProcessor, Page};
+use crate::theme::Theme;
+use crate::utils::{AsciiArtGenerator, HashUtils};
+
+/// Open Graph's recommended 1.91:1 image size
+const CARD_WIDTH: u32 = 1200;
+const CARD_HEIGHT: u32 = 630;
+const MARGIN: f32 = 80.0;
+
+/// Bump when the card layout changes so cached cards are re-rendered
+const CARD_VERSION: &str = "1";
+
+const SANS_BOLD: &[u8] = include_bytes!("../../themes/fonts/DejaVuSans-Bold.ttf");
+const MONO: &[u8] = include_bytes!("../../themes/fonts/DejaVuSansMono.ttf");
+
+/// Every string drawn on a card, which together with the theme and `CARD_VERSION` decides
+/// whether a cached render can be reused
+struct CardText {
+    blog_title: String,
+    title: String,
+    /// Shell prompt naming the page's URL, drawn by the Hacker card only
+    prompt: String,
+    /// Date and reading time line for posts
+    footer: String,
+}
+
+impl CardText {
+    fn new(page: &Page, theme: &Theme, config: &Config) -> Self {
+        let footer = match page.date {
+            Some(date) => format!("{} · {} min read", date.format("%B %-d, %Y"), page.reading_time),
+            None => String::new(),
+        };
+        let (title, prompt) = match theme {
+            Theme::Vercel => (page.title.clone(), String::new()),
+            Theme::Hacker => (format!(">>> {} <<<", page.title), format!("$ cat {}", page.url)),
+        };
+        Self { blog_title: config.blog_title.clone(), title, prompt, footer }
+    }
+
+    fn cache_key(&self, theme: &Theme) -> String {
+        [CARD_VERSION, &theme.to_string(), &self.blog_title, &self.title, &self.prompt, &self.footer].join("\0")
+    }
+}
+
+/// Renders per-page PNG social cards with embedded fonts, no network or system fonts needed
+pub struct SocialCardGenerator {
+    sans: FontRef<'static>,
+    mono: FontRef<'static>,
+}
+
+impl SocialCardGenerator {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            sans: FontRef::try_from_slice(SANS_BOLD).map_err(|e| anyhow!("Invalid embedded font: {}", e))?,
+            mono: FontRef::try_from_slice(MONO).map_err(|e| anyhow!("Invalid embedded font: {}", e))?,
+        })
+    }
+
+    /// Write the card for a page to `/assets/social/<hash>.png`, reusing a cached render when
+    /// the card's content hasn't changed. Returns the site-relative path and whether it was cached.
+    pub fn generate(&self, page: &Page, theme: &Theme, config: &Config, output_dir: &str) -> Result<(String, bool)> {
+        let text = CardText::new(page, theme, config);
+        let hash = &HashUtils::sha256_hex(text.cache_key(theme).as_bytes())[..16];
+
+        let cache_path = Path::new(&config.social_cards.cache_dir).join(format!("{}.png", hash));
+        let cached = cache_path.exists();
+        if !cached {
+            let card = match theme {
+                Theme::Vercel => self.render_vercel(&text),
+                Theme::Hacker => self.render_hacker(&text),
+            };
+            fs::create_dir_all(&config.social_cards.cache_dir)?;
+            card.save(&cache_path)?;
+        }
+
+        let url = format!("/assets/social/{}.png", hash);
+        let output_path = format!("{}{}", output_dir, url);
+        ContentProcessor::ensure_output_dir(&output_path)?;
+        fs::copy(&cache_path, &output_path)?;
+        Ok((url, cached))
+    }
+
+    /// White card with an accent bar and large bold title, matching the Vercel theme
+    fn render_vercel(&self, text: &CardText) -> RgbaImage {
+        let mut card = RgbaImage::from_pixel(CARD_WIDTH, CARD_HEIGHT, Rgba([255, 255, 255, 255]));
+        Self::fill_rect(&mut card, 0, 0, CARD_WIDTH, 12, Rgba([0, 112, 243, 255]));
+
+        Self::draw_text(&mut card, &self.sans, 34.0, MARGIN, 120.0, Rgba([102, 102, 102, 255]), &text.blog_title);
+
+        let (size, lines) = Self::fit_title(&self.sans, &text.title, 72.0, 44.0, 3);
+        let mut baseline = 240.0;
+        for line in &lines {
+            Self::draw_text(&mut card, &self.sans, size, MARGIN, baseline, Rgba([0, 0, 0, 255]), line);
+            baseline += size * 1.25;
+        }
+
+        Self::draw_text(&mut card, &self.sans, 28.0, MARGIN, CARD_HEIGHT as f32 - MARGIN, Rgba([153, 153, 153, 255]), &text.footer);
+        card
+    }
+
+    /// Terminal card with the blog title as the Hacker theme's ASCII art banner
+    fn render_hacker(&self, text: &CardText) -> RgbaImage {
+        let green = Rgba([0, 255, 0, 255]);
+        let mut card = RgbaImage::from_pixel(CARD_WIDTH, CARD_HEIGHT, Rgba([10, 10, 10, 255]));
+
+        // Banner sized to fill the width, capped so the title still fits below it
+        let ascii_art = AsciiArtGenerator::generate_ascii_art(&text.blog_title);
+        let banner: Vec<&str> = ascii_art.trim_matches('\n').lines().collect();
+        let columns = banner.iter().map(|line| line.chars().count()).max().unwrap_or(1).max(1);
+        let advance = self.mono.as_scaled(PxScale::from(100.0)).h_advance(self.mono.glyph_id('█')) / 100.0;
+        let banner_size = ((CARD_WIDTH as f32 - 2.0 * MARGIN) / (columns as f32 * advance)).min(34.0);
+
+        let mut baseline = MARGIN;
+        for line in &banner {
+            Self::draw_text(&mut card, &self.mono, banner_size, MARGIN, baseline, green, line);
+            baseline += banner_size;
+        }
+
+        let (size, lines) = Self::fit_title(&self.mono, &text.title, 52.0, 32.0, 3);
+        baseline += size * 1.5;
+        for line in &lines {
+            Self::draw_text(&mut card, &self.mono, size, MARGIN, baseline, green, line);
+            baseline += size * 1.3;
+        }
+
+        Self::draw_text(&mut card, &self.mono, 26.0, MARGIN, CARD_HEIGHT as f32 - MARGIN - 40.0, Rgba([0, 136, 0, 255]), &text.prompt);
+        Self::draw_text(&mut card, &self.mono, 26.0, MARGIN, CARD_HEIGHT as f32 - MARGIN, Rgba([0, 136, 0, 255]), &text.footer);
+
+        // CRT scanlines, like the theme's background gradient
+        for y in (0..CARD_HEIGHT).step_by(4) {
+            for x in 0..CARD_WIDTH {
+                let pixel = card.get_pixel_mut(x, y);
+                for channel in 0..3 {
+                    pixel[channel] = (pixel[channel] as f32 * 0.8) as u8;
+                }
+            }
+        }
+        card
+    }
+
+    /// Largest font size between `max` and `min` at which the text wraps into at most
+    /// `max_lines` lines; at `min` the last line is truncated with an ellipsis
+    fn fit_title(font: &FontRef, text: &str, max: f32, min: f32, max_lines: usize) -> (f32, Vec<String>) {
+        let width = CARD_WIDTH as f32 - 2.0 * MARGIN;
+        let mut size = max;
+        loop {
+            let mut lines = Self::wrap(font, size, text, width);
+            if lines.len() <= max_lines {
+                return (size, lines);
+            }
+            if size <= min {
+                lines.truncate(max_lines);
+                if let Some(last) = lines.last_mut() {
+                    while !last.is_empty() && Self::text_width(font, size, &format!("{}…", last)) > width {
+                        last.pop();
+                    }
+                    last.push('…');
+                }
+                return (size, lines);
+            }
+            size = (size - 4.0).max(min);
+        }
+    }
+
+    /// Greedy word wrap to the given pixel width
+    fn wrap(font: &FontRef, size: f32, text: &str, width: f32) -> Vec<String> {
+        let mut lines: Vec<String> = Vec::new();
+        let mut current = String::new();
+        for word in text.split_whitespace() {
+            let candidate = if current.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", current, word)
+            };
+            if !current.is_empty() && Self::text_width(font, size, &candidate) > width {
+                lines.push(std::mem::replace(&mut current, word.to_string()));
+            } else {
+                current = candidate;
+            }
+        }
+        if !current.is_empty() {
+            lines.push(current);
+        }
+        lines
+    }
+
+    fn text_width(font: &FontRef, size: f32, text: &str) -> f32 {
+        let scaled = font.as_scaled(PxScale::from(size));
+        let mut width = 0.0;
+        let mut previous = None;
+        for ch in text.chars() {
+            let id = scaled.glyph_id(ch);
+            if let Some(previous) = previous {
+                width += scaled.kern(previous, id);
+            }
+            width += scaled.h_advance(id);
+            previous = Some(id);
+        }
+        width
+    }
+
+    /// Draw one line of text with its baseline at `y`, alpha-blending glyph coverage
+    fn draw_text(card: &mut RgbaImage, font: &FontRef, size: f32, x: f32, y: f32, color: Rgba<u8>, text: &str) {
+        let scaled = font.as_scaled(PxScale::from(size));
+        let mut caret = x;
+        let mut previous = None;
+
+        for ch in text.chars() {
+            let id = scaled.glyph_id(ch);
+            if let Some(previous) = previous {
+                caret += scaled.kern(previous, id);
+            }
+            let glyph = id.with_scale_and_position(size, point(caret, y));
+            caret += scaled.h_advance(id);
+            previous = Some(id);
+
+            let Some(outlined) = font.outline_glyph(glyph) else {
+                continue;
+            };
+            let bounds = outlined.px_bounds();
+            outlined.draw(|gx, gy, coverage| {
+                let px = bounds.min.x as i32 + gx as i32;
+                let py = bounds.min.y as i32 + gy as i32;
+                if px < 0 || py < 0 || px >= CARD_WIDTH as i32 || py >= CARD_HEIGHT as i32 {
+                    return;
+                }
+                let pixel = card.get_pixel_mut(px as u32, py as u32);
+                let alpha = coverage.clamp(0.0, 1.0);
+                for channel in 0..3 {
+                    pixel[channel] = (pixel[channel] as f32 * (1.0 - alpha) + color[channel] as f32 * alpha) as u8;
+                }
+            });
+        }
+    }
+
+    fn fill_rect(card: &mut RgbaImage, x: u32, y: u32, width: u32, height: u32, color: Rgba<u8>) {
+        for py in y..(y + height).min(CARD_HEIGHT) {
+            for px in x..(x + width).min(CARD_WIDTH) {
+                card.put_pixel(px, py, color);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hacker_cache_key_changes_with_url() {
+        let config = Config::default();
+        let old = Page { title: "Post".to_string(), url: "/posts/old.html".to_string(), ..Page::default() };
+        let new = Page { title: "Post".to_string(), url: "/posts/new.html".to_string(), ..Page::default() };
+
+        let key = |page: &Page, theme| CardText::new(page, &theme, &config).cache_key(&theme);
+        assert_ne!(key(&old, Theme::Hacker), key(&new, Theme::Hacker));
+        // The Vercel card doesn't show the URL, so moving a post keeps its card
+        assert_eq!(key(&old, Theme::Vercel), key(&new, Theme::Vercel));
+    }
+}
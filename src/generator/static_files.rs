@@ -0,0 +1,68 @@
+use anyhow::Result;
+use std::fs;
+use std::path::Path;
+
+use crate::content::ContentProcessor;
+
+pub struct StaticFiles;
+
+impl StaticFiles {
+    /// Copy every file under `source_dir` into the output directory verbatim, keeping relative
+    /// paths. Dotfiles (`.well-known/`) and markdown are copied too. A missing directory
+    /// copies nothing.
+    pub fn copy_dir(source_dir: &str, output_dir: &str) -> Result<usize> {
+        if !Path::new(source_dir).is_dir() {
+            return Ok(0);
+        }
+        Self::copy_files(&ContentProcessor::collect_files(source_dir)?, source_dir, output_dir)
+    }
+
+    /// Copy images, downloads and other files that sit next to the markdown sources, so
+    /// relative links from a page (or a page bundle's `index.md`) keep working
+    pub fn copy_content_assets(input_dir: &str, output_dir: &str) -> Result<usize> {
+        Self::copy_files(&ContentProcessor::collect_assets(input_dir)?, input_dir, output_dir)
+    }
+
+    fn copy_files(files: &[String], source_dir: &str, output_dir: &str) -> Result<usize> {
+        for file in files {
+            let relative = Path::new(file).strip_prefix(source_dir).unwrap_or(Path::new(file));
+            let output_path = Path::new(output_dir).join(relative);
+            ContentProcessor::ensure_output_dir(&output_path.to_string_lossy())?;
+            fs::copy(file, &output_path)?;
+        }
+        Ok(files.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TestDir;
+
+    #[test]
+    fn static_dir_is_copied_verbatim() {
+        let dir = TestDir::new("static");
+        dir.write("static/.well-known/security.txt", "Contact: mailto:security@example.com");
+        dir.write("static/notes.md", "# Notes");
+        dir.write("static/img/logo.png", "png");
+
+        assert_eq!(StaticFiles::copy_dir(&dir.join("static"), &dir.join("public")).unwrap(), 3);
+        assert_eq!(dir.read("public/.well-known/security.txt"), "Contact: mailto:security@example.com");
+        assert_eq!(dir.read("public/notes.md"), "# Notes");
+        assert_eq!(dir.read("public/img/logo.png"), "png");
+    }
+
+    #[test]
+    fn content_assets_skip_markdown_and_hidden_files() {
+        let dir = TestDir::new("content-assets");
+        dir.write("content/posts/bundle/index.md", "# Post");
+        dir.write("content/posts/bundle/diagram.png", "png");
+        dir.write("content/.drafts/secret.png", "png");
+        dir.write("content/posts/.DS_Store", "");
+
+        assert_eq!(StaticFiles::copy_content_assets(&dir.join("content"), &dir.join("public")).unwrap(), 1);
+        assert!(dir.path().join("public/posts/bundle/diagram.png").is_file());
+        assert!(!dir.path().join("public/posts/bundle/index.md").exists());
+        assert!(!dir.path().join("public/.drafts").exists());
+    }
+}
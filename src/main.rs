@@ -2,12 +2,14 @@ use anyhow::Result;
 use clap::Parser;
 
 mod server;
+mod watcher;
 mod config;
 mod cli;
 mod theme;
 mod generator;
 mod content;
 mod utils;
+mod check;
 
 use cli::{Cli, CommandHandler};
 
@@ -1,29 +1,113 @@
 use std::fs;
+use std::path::Path;
 use anyhow::Result;
+use crate::config::Config;
+use super::manifest::ThemeManifest;
+use super::sass::{SassCompiler, THEME_ENTRY_POINT};
+use super::toggle::{ThemeToggle, TOGGLE_SCRIPT, TOGGLE_SCRIPT_URL};
 use super::Theme;
 
+/// A stylesheet linked from every page
+#[derive(Debug, Clone)]
+pub struct Stylesheet {
+    pub href: String,
+    /// Theme the stylesheet belongs to; `None` for the site's own Sass
+    pub theme: Option<Theme>,
+}
+
+/// What the page template needs to load the theme
+#[derive(Debug, Clone)]
+pub struct SiteStyles {
+    /// The site theme's stylesheet first, then other themes' in use, then the site's Sass
+    pub stylesheets: Vec<Stylesheet>,
+    pub toggle: Option<ThemeToggle>,
+}
+
+impl SiteStyles {
+    /// The toggle, when it offers the theme a page is in
+    pub fn toggle_for(&self, theme: &Theme) -> Option<&ThemeToggle> {
+        self.toggle.as_ref().filter(|toggle| toggle.default_variant(theme).is_some())
+    }
+}
+
 pub struct ThemeAssets;
 
 impl ThemeAssets {
-    /// Copy theme assets (CSS, etc.) to the output directory
-    pub fn copy_theme_assets(theme: &Theme, output_dir: &str) -> Result<()> {
-        let theme_css = Self::get_theme_css(theme);
-        let css_path = format!("{}/assets/style.css", output_dir);
-        
+    /// Copy theme assets (CSS, etc.) to the output directory and compile the site's Sass.
+    /// The site's `[theme_options]` are appended to each theme stylesheet declaring them as
+    /// custom property overrides. Other themes, used by pages or offered by `[theme_toggle]`, each get their
+    /// own stylesheet at `/assets/theme-{name}.css`.
+    pub fn copy_theme_assets(theme: &Theme, page_themes: &[Theme], config: &Config, output_dir: &str) -> Result<SiteStyles> {
+        let toggle = ThemeToggle::from_config(config, theme)?;
+        let mut themes = vec![*theme];
+        let toggle_themes = toggle.as_ref().map(ThemeToggle::themes).unwrap_or_default();
+        for other in toggle_themes.iter().chain(page_themes) {
+            if !themes.contains(other) {
+                themes.push(*other);
+            }
+        }
+
         // Create assets directory
         fs::create_dir_all(format!("{}/assets", output_dir))?;
-        
-        // Write CSS file
-        fs::write(css_path, theme_css)?;
-        
-        Ok(())
+
+        let mut stylesheets = Vec::new();
+        for current in &themes {
+            let href = if current == theme {
+                "/assets/style.css".to_string()
+            } else {
+                format!("/assets/theme-{}.css", current)
+            };
+            fs::write(format!("{}{}", output_dir, href), Self::theme_stylesheet(current, current == theme, toggle.as_ref(), config)?)?;
+            stylesheets.push(Stylesheet { href, theme: Some(*current) });
+        }
+
+        if toggle.is_some() {
+            fs::write(format!("{}{}", output_dir, TOGGLE_SCRIPT_URL), TOGGLE_SCRIPT)?;
+        }
+
+        let theme_urls: Vec<&str> = stylesheets.iter().map(|stylesheet| stylesheet.href.as_str()).collect();
+        let sass_urls = SassCompiler::compile_dir(&config.build.sass_dir, output_dir, &theme_urls)?;
+        stylesheets.extend(sass_urls.into_iter().map(|href| Stylesheet { href, theme: None }));
+        Ok(SiteStyles { stylesheets, toggle })
     }
 
-    /// Get the CSS content for a specific theme
-    fn get_theme_css(theme: &Theme) -> String {
-        match theme {
+    /// A theme's CSS with the site's options, the palettes of the toggle's color schemes and,
+    /// when the toggle switches themes, a rule hiding markup meant for the other themes.
+    /// Options the site theme doesn't declare are an error; other themes skip them.
+    fn theme_stylesheet(theme: &Theme, site_theme: bool, toggle: Option<&ThemeToggle>, config: &Config) -> Result<String> {
+        let manifest = ThemeManifest::load(theme, &config.build.theme_dir)?;
+        let options = if site_theme {
+            config.theme_options.clone()
+        } else {
+            manifest.declared_options(&config.theme_options)
+        };
+        let mut css = Self::get_theme_css(theme, &config.build.theme_dir)?;
+        css.push_str(&manifest.css_overrides(&options)?);
+
+        if let Some(toggle) = toggle.filter(|toggle| toggle.default_variant(theme).is_some()) {
+            css.push_str(&manifest.scheme_rules(&toggle.schemes(theme), &options)?);
+            if toggle.switches_themes() {
+                css.push_str(&format!(
+                    "\n/* Markup for other themes */\n[data-theme-only]:not([data-theme-only=\"{}\"]) {{\n  display: none !important;\n}}\n",
+                    theme
+                ));
+            }
+        }
+        Ok(css)
+    }
+
+    /// Get the CSS content for a specific theme. A site can replace a built-in theme's
+    /// stylesheet with `{theme_dir}/{theme}/style.scss`, which may use partials next to it.
+    fn get_theme_css(theme: &Theme, theme_dir: &str) -> Result<String> {
+        let local_theme = Path::new(theme_dir).join(theme.to_string());
+        let entry_point = local_theme.join(THEME_ENTRY_POINT);
+        if entry_point.is_file() {
+            return SassCompiler::compile(&entry_point, &local_theme);
+        }
+
+        Ok(match theme {
             Theme::Vercel => include_str!("../../themes/vercel.css").to_string(),
             Theme::Hacker => include_str!("../../themes/hacker.css").to_string(),
-        }
+        })
     }
-}
\ No newline at end of file
+}
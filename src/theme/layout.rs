@@ -0,0 +1,30 @@
+/// Page template, chosen with front-matter `layout` or a section's `_index.md`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Layout {
+    /// Site header with the page title and post metadata, for posts and pages
+    #[default]
+    Page,
+    /// Large header introducing the site with its description, for the home page
+    Landing,
+}
+
+impl std::str::FromStr for Layout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "page" => Ok(Layout::Page),
+            "landing" => Ok(Layout::Landing),
+            _ => Err(format!("Unknown layout: {}. Available layouts: page, landing", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for Layout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Layout::Page => write!(f, "page"),
+            Layout::Landing => write!(f, "landing"),
+        }
+    }
+}
\ No newline at end of file
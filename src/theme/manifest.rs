@@ -0,0 +1,341 @@
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use super::Theme;
+
+/// Replaces the built-in manifest when a site overrides a theme in its theme directory
+pub const MANIFEST_FILE: &str = "theme.toml";
+
+/// CSS named colors accepted by `color` options, besides hex and functional notation
+const NAMED_COLORS: &[&str] = &[
+    "aliceblue", "antiquewhite", "aqua", "aquamarine", "azure", "beige", "bisque", "black", "blanchedalmond",
+    "blue", "blueviolet", "brown", "burlywood", "cadetblue", "chartreuse", "chocolate", "coral", "cornflowerblue",
+    "cornsilk", "crimson", "cyan", "darkblue", "darkcyan", "darkgoldenrod", "darkgray", "darkgreen", "darkgrey",
+    "darkkhaki", "darkmagenta", "darkolivegreen", "darkorange", "darkorchid", "darkred", "darksalmon",
+    "darkseagreen", "darkslateblue", "darkslategray", "darkslategrey", "darkturquoise", "darkviolet", "deeppink",
+    "deepskyblue", "dimgray", "dimgrey", "dodgerblue", "firebrick", "floralwhite", "forestgreen", "fuchsia",
+    "gainsboro", "ghostwhite", "gold", "goldenrod", "gray", "green", "greenyellow", "grey", "honeydew", "hotpink",
+    "indianred", "indigo", "ivory", "khaki", "lavender", "lavenderblush", "lawngreen", "lemonchiffon", "lightblue",
+    "lightcoral", "lightcyan", "lightgoldenrodyellow", "lightgray", "lightgreen", "lightgrey", "lightpink",
+    "lightsalmon", "lightseagreen", "lightskyblue", "lightslategray", "lightslategrey", "lightsteelblue",
+    "lightyellow", "lime", "limegreen", "linen", "magenta", "maroon", "mediumaquamarine", "mediumblue",
+    "mediumorchid", "mediumpurple", "mediumseagreen", "mediumslateblue", "mediumspringgreen", "mediumturquoise",
+    "mediumvioletred", "midnightblue", "mintcream", "mistyrose", "moccasin", "navajowhite", "navy", "oldlace",
+    "olive", "olivedrab", "orange", "orangered", "orchid", "palegoldenrod", "palegreen", "paleturquoise",
+    "palevioletred", "papayawhip", "peachpuff", "peru", "pink", "plum", "powderblue", "purple", "rebeccapurple",
+    "red", "rosybrown", "royalblue", "saddlebrown", "salmon", "sandybrown", "seagreen", "seashell", "sienna",
+    "silver", "skyblue", "slateblue", "slategray", "slategrey", "snow", "springgreen", "steelblue", "tan", "teal",
+    "thistle", "tomato", "turquoise", "violet", "wheat", "white", "whitesmoke", "yellow", "yellowgreen",
+];
+
+/// Color functions accepted by `color` options
+const COLOR_FUNCTIONS: &[&str] = &["rgb", "rgba", "hsl", "hsla", "hwb", "lab", "lch", "oklab", "oklch"];
+
+/// Units accepted by `length` options
+const LENGTH_UNITS: &[&str] = &["px", "rem", "em", "ch", "vw", "%"];
+
+/// A theme's `theme.toml`: the options a site may set and the color schemes it ships
+#[derive(Debug, Deserialize)]
+pub struct ThemeManifest {
+    pub name: String,
+    #[serde(default)]
+    pub options: BTreeMap<String, ThemeOption>,
+    /// Custom property values for each palette, keyed by scheme name (`light`, `dark`)
+    #[serde(default)]
+    pub color_schemes: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+/// An option declared by a theme
+#[derive(Debug, Deserialize)]
+pub struct ThemeOption {
+    #[serde(rename = "type")]
+    pub kind: OptionKind,
+    /// Value the theme's CSS uses when the site doesn't set the option
+    pub default: String,
+    /// Custom properties set to the option's value
+    #[serde(default)]
+    pub properties: Vec<String>,
+    /// Custom properties computed from the option's value, `{value}` standing for it. Options
+    /// setting the same property directly win.
+    #[serde(default)]
+    pub derived: BTreeMap<String, String>,
+    #[serde(default)]
+    pub description: String,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum OptionKind {
+    /// Hex, functional notation (`rgb()`, `hsl()`, …) or a named color
+    Color,
+    /// Comma-separated font families, quoted where they contain spaces
+    Font,
+    /// Positive length in `px`, `rem`, `em`, `ch`, `vw` or `%`
+    Length,
+    /// Name of one of the manifest's `color_schemes`, whose palette it applies
+    ColorScheme,
+}
+
+impl ThemeManifest {
+    /// Load the manifest of a theme, preferring `{theme_dir}/{theme}/theme.toml` over the
+    /// built-in one
+    pub fn load(theme: &Theme, theme_dir: &str) -> Result<Self> {
+        let local = Path::new(theme_dir).join(theme.to_string()).join(MANIFEST_FILE);
+        if local.is_file() {
+            let source = fs::read_to_string(&local)?;
+            return toml::from_str(&source).with_context(|| format!("Invalid theme manifest {}", local.display()));
+        }
+
+        let source = match theme {
+            Theme::Vercel => include_str!("../../themes/vercel.toml"),
+            Theme::Hacker => include_str!("../../themes/hacker.toml"),
+        };
+        Ok(toml::from_str(source)?)
+    }
+
+    /// Validate the site's `[theme_options]` and render them as a `:root` rule overriding the
+    /// theme's custom properties. A color scheme comes first so explicit options win over its
+    /// palette. Returns an empty string when no options are set.
+    pub fn css_overrides(&self, values: &BTreeMap<String, toml::Value>) -> Result<String> {
+        let (scheme, declarations) = self.option_declarations(values)?;
+        // The stylesheet is written in the default scheme already
+        let mut lines = match scheme {
+            Some(scheme) if Some(scheme) != self.scheme_option().map(|(_, option)| option.default.as_str()) => {
+                self.palette(scheme)
+            }
+            _ => Vec::new(),
+        };
+
+        if lines.is_empty() && declarations.is_empty() {
+            return Ok(String::new());
+        }
+        lines.extend(declarations);
+        Ok(format!("\n/* Theme options */\n:root {{\n{}\n}}\n", lines.join("\n")))
+    }
+
+    /// Rules applying each scheme's palette when `<html>` has `data-color-scheme` set to it,
+    /// as the theme toggle does. The site's other options are repeated so they still win.
+    pub fn scheme_rules(&self, schemes: &[&str], values: &BTreeMap<String, toml::Value>) -> Result<String> {
+        let (_, declarations) = self.option_declarations(values)?;
+        let mut css = String::from("\n/* Color schemes */\n");
+        for scheme in schemes {
+            let mut lines = self.palette(scheme);
+            lines.extend(declarations.iter().cloned());
+            css.push_str(&format!(":root[data-color-scheme=\"{}\"] {{\n{}\n}}\n", scheme, lines.join("\n")));
+        }
+        Ok(css)
+    }
+
+    /// The options in `values` this theme declares. Other themes in use share the site's
+    /// `[theme_options]`, which are checked against the site theme only.
+    pub fn declared_options(&self, values: &BTreeMap<String, toml::Value>) -> BTreeMap<String, toml::Value> {
+        values
+            .iter()
+            .filter(|(name, _)| self.options.contains_key(*name))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect()
+    }
+
+    /// The scheme the theme's `:root` is in: the site's `color_scheme` option, else the
+    /// manifest default
+    pub fn default_scheme(&self, values: &BTreeMap<String, toml::Value>) -> Option<String> {
+        let (name, option) = self.scheme_option()?;
+        let value = values.get(name).and_then(toml::Value::as_str).unwrap_or(&option.default);
+        Some(value.trim().to_string())
+    }
+
+    /// Validate the site's options, returning the chosen color scheme and the custom property
+    /// declarations for the rest
+    fn option_declarations<'v>(&self, values: &'v BTreeMap<String, toml::Value>) -> Result<(Option<&'v str>, Vec<String>)> {
+        let mut scheme = None;
+        let mut derived = Vec::new();
+        let mut declarations = Vec::new();
+
+        for (name, value) in values {
+            let option = self.options.get(name).ok_or_else(|| {
+                let available: Vec<String> = self
+                    .options
+                    .iter()
+                    .map(|(name, option)| format!("{} ({})", name, option.description))
+                    .collect();
+                anyhow!(
+                    "Unknown theme option '{}' for theme {}. Available options: {}",
+                    name,
+                    self.name,
+                    available.join(", ")
+                )
+            })?;
+            let value = value
+                .as_str()
+                .ok_or_else(|| anyhow!("Theme option '{}' must be a string, got {}", name, value))?
+                .trim();
+            self.validate(name, option, value)?;
+
+            if option.kind == OptionKind::ColorScheme {
+                scheme = Some(value);
+            }
+            for property in &option.properties {
+                declarations.push(format!("  {}: {};", property, value));
+            }
+            for (property, template) in &option.derived {
+                derived.push(format!("  {}: {};", property, template.replace("{value}", value)));
+            }
+        }
+
+        // Derived values come first so options setting the same property override them
+        derived.extend(declarations);
+        Ok((scheme, derived))
+    }
+
+    fn scheme_option(&self) -> Option<(&String, &ThemeOption)> {
+        self.options.iter().find(|(_, option)| option.kind == OptionKind::ColorScheme)
+    }
+
+    /// `color-scheme` and the custom properties of a palette
+    fn palette(&self, scheme: &str) -> Vec<String> {
+        let mut lines = vec![format!("  color-scheme: {};", scheme)];
+        if let Some(palette) = self.color_schemes.get(scheme) {
+            lines.extend(palette.iter().map(|(property, color)| format!("  {}: {};", property, color)));
+        }
+        lines
+    }
+
+    fn validate(&self, name: &str, option: &ThemeOption, value: &str) -> Result<()> {
+        let (valid, expected) = match option.kind {
+            OptionKind::Color => (Self::is_color(value), "a hex, rgb()/hsl() or named color".to_string()),
+            OptionKind::Font => (Self::is_font_stack(value), "comma-separated font families".to_string()),
+            OptionKind::Length => (Self::is_length(value), format!("a length in {}", LENGTH_UNITS.join(", "))),
+            OptionKind::ColorScheme => {
+                let schemes: Vec<&str> = self.color_schemes.keys().map(String::as_str).collect();
+                (self.color_schemes.contains_key(value), format!("one of {}", schemes.join(", ")))
+            }
+        };
+        if !valid {
+            bail!("Invalid theme option {} = \"{}\" for theme {}: expected {}", name, value, self.name, expected);
+        }
+        Ok(())
+    }
+
+    fn is_color(value: &str) -> bool {
+        if let Some(hex) = value.strip_prefix('#') {
+            return matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit());
+        }
+        let lower = value.to_ascii_lowercase();
+        if let Some((function, arguments)) = lower.split_once('(') {
+            return COLOR_FUNCTIONS.contains(&function)
+                && arguments.strip_suffix(')').is_some_and(|arguments| {
+                    !arguments.trim().is_empty()
+                        && arguments.chars().all(|c| c.is_ascii_alphanumeric() || " .,%/+-".contains(c))
+                });
+        }
+        NAMED_COLORS.contains(&lower.as_str())
+    }
+
+    /// Families are CSS identifiers (`Georgia`, `sans-serif`, `Fira Sans`) or quoted strings
+    fn is_font_stack(value: &str) -> bool {
+        value.split(',').all(|family| {
+            let family = family.trim();
+            let quoted = ['"', '\''].iter().any(|&quote| {
+                family.len() >= 2
+                    && family.starts_with(quote)
+                    && family.ends_with(quote)
+                    && !family[1..family.len() - 1].contains([quote, '\\'])
+            });
+            quoted
+                || (!family.is_empty()
+                    && !family.starts_with(|c: char| c.is_ascii_digit())
+                    && family.chars().all(|c| c.is_alphanumeric() || c == ' ' || c == '-' || c == '_'))
+        })
+    }
+
+    fn is_length(value: &str) -> bool {
+        LENGTH_UNITS.iter().any(|unit| {
+            value
+                .strip_suffix(unit)
+                .filter(|number| !number.is_empty() && number.chars().all(|c| c.is_ascii_digit() || c == '.'))
+                .and_then(|number| number.parse::<f64>().ok())
+                .is_some_and(|number| number > 0.0)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(pairs: &[(&str, &str)]) -> BTreeMap<String, toml::Value> {
+        pairs.iter().map(|(name, value)| (name.to_string(), toml::Value::String(value.to_string()))).collect()
+    }
+
+    fn overrides(pairs: &[(&str, &str)]) -> Result<String> {
+        ThemeManifest::load(&Theme::Vercel, "").unwrap().css_overrides(&options(pairs))
+    }
+
+    #[test]
+    fn accent_color_derives_a_hover_color() {
+        let css = overrides(&[("accent_color", "#ff0000")]).unwrap();
+        assert!(css.contains("--accent-color: #ff0000;"));
+        assert!(css.contains("--accent-hover: color-mix(in srgb, #ff0000 80%, black);"));
+    }
+
+    #[test]
+    fn explicit_accent_hover_wins_over_derived() {
+        let css = overrides(&[("accent_color", "#ff0000"), ("accent_hover", "#aa0000")]).unwrap();
+        let derived = css.find("--accent-hover: color-mix").unwrap();
+        let explicit = css.find("--accent-hover: #aa0000").unwrap();
+        assert!(derived < explicit);
+    }
+
+    #[test]
+    fn no_options_means_no_overrides() {
+        assert_eq!(overrides(&[]).unwrap(), "");
+        assert_eq!(overrides(&[("color_scheme", "light")]).unwrap(), "");
+        assert!(overrides(&[("color_scheme", "dark")]).unwrap().contains("color-scheme: dark;"));
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        for (name, value) in [
+            ("accent_color", "red; } body { display: none"),
+            ("accent_color", "#12345"),
+            ("accent_color", "url(x)"),
+            ("font", "\"Fira Sans\"; color: red"),
+            ("content_width", "-10px"),
+            ("content_width", "10"),
+            ("color_scheme", "sepia"),
+        ] {
+            let error = overrides(&[(name, value)]).unwrap_err().to_string();
+            assert!(error.starts_with(&format!("Invalid theme option {} = ", name)), "{}", error);
+        }
+    }
+
+    #[test]
+    fn accepts_valid_values() {
+        for (name, value) in [
+            ("accent_color", "#0af"),
+            ("accent_color", "rgb(10 20 30 / 50%)"),
+            ("accent_color", "RebeccaPurple"),
+            ("font", "\"Fira Sans\", Georgia, sans-serif"),
+            ("content_width", "42.5rem"),
+            ("content_width", "80%"),
+        ] {
+            assert!(overrides(&[(name, value)]).is_ok(), "{} = {}", name, value);
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_options_and_non_strings() {
+        let error = overrides(&[("accent", "#fff")]).unwrap_err().to_string();
+        assert!(error.starts_with("Unknown theme option 'accent' for theme vercel. Available options: accent_color ("));
+
+        let manifest = ThemeManifest::load(&Theme::Vercel, "").unwrap();
+        let values = BTreeMap::from([("content_width".to_string(), toml::Value::Integer(700))]);
+        assert_eq!(
+            manifest.css_overrides(&values).unwrap_err().to_string(),
+            "Theme option 'content_width' must be a string, got 700"
+        );
+    }
+}
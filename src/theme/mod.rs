@@ -1,5 +1,10 @@
 pub mod theme_type;
+pub mod layout;
 pub mod assets;
+pub mod sass;
+pub mod manifest;
+pub mod toggle;
 
 pub use theme_type::Theme;
-pub use assets::ThemeAssets;
\ No newline at end of file
+pub use layout::Layout;
+pub use assets::{SiteStyles, ThemeAssets};
\ No newline at end of file
@@ -0,0 +1,127 @@
+use anyhow::{anyhow, bail, Result};
+use glob::glob;
+use grass::{ErrorKind, OutputStyle};
+use std::fs;
+use std::path::Path;
+
+/// Stylesheet a site-local theme directory compiles into `/assets/style.css`
+pub const THEME_ENTRY_POINT: &str = "style.scss";
+
+pub struct SassCompiler;
+
+impl SassCompiler {
+    /// Compile one `.scss` or `.sass` file. `@use` and `@import` resolve relative to the file
+    /// and to `load_path`, so partials (`_name.scss`) can live next to the entry point.
+    /// Errors read `file:line:column: message`.
+    pub fn compile(path: &Path, load_path: &Path) -> Result<String> {
+        let options = grass::Options::default()
+            .style(OutputStyle::Expanded)
+            .load_path(load_path)
+            .quiet(true);
+
+        grass::from_path(path, &options).map_err(|error| match error.kind() {
+            ErrorKind::ParseError { message, loc, .. } => anyhow!(
+                "Sass error in {}:{}:{}: {}",
+                loc.file.name(),
+                loc.begin.line + 1,
+                loc.begin.column + 1,
+                message
+            ),
+            ErrorKind::IoError(error) => anyhow!("Sass error in {}: {}", path.display(), error),
+            ErrorKind::FromUtf8Error(_) => anyhow!("Sass error in {}: invalid UTF-8", path.display()),
+            _ => anyhow!("Sass error in {}", path.display()),
+        })
+    }
+
+    /// Compile every entry point in `sass_dir` to the same relative path under
+    /// `{output}/assets/` (`sass/blog/post.scss` → `/assets/blog/post.css`). Partials are
+    /// only compiled through the files using them. Entry points that would overwrite one of
+    /// the `reserved` URLs, such as the theme's `/assets/style.css`, or each other are an
+    /// error. Returns the URLs of the stylesheets written, sorted so pages link them in a
+    /// stable order.
+    pub fn compile_dir(sass_dir: &str, output_dir: &str, reserved: &[&str]) -> Result<Vec<String>> {
+        let root = Path::new(sass_dir);
+        if !root.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut urls = Vec::new();
+        for extension in ["scss", "sass"] {
+            for entry in glob(&format!("{}/**/*.{}", sass_dir, extension))? {
+                let path = entry?;
+                if Self::is_partial(&path) {
+                    continue;
+                }
+
+                let relative = path.strip_prefix(root)?.with_extension("css");
+                let parts: Vec<String> = relative
+                    .components()
+                    .map(|part| part.as_os_str().to_string_lossy().into_owned())
+                    .collect();
+                let url = format!("/assets/{}", parts.join("/"));
+                if reserved.contains(&url.as_str()) {
+                    bail!("Sass stylesheet {} would overwrite the theme stylesheet {}; rename it", path.display(), url);
+                }
+                if urls.contains(&url) {
+                    bail!("Sass stylesheet {} would overwrite {} compiled from another file", path.display(), url);
+                }
+
+                let css = Self::compile(&path, root)?;
+                let output_path = Path::new(output_dir).join("assets").join(&relative);
+                if let Some(parent) = output_path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&output_path, css)?;
+                urls.push(url);
+            }
+        }
+
+        urls.sort();
+        Ok(urls)
+    }
+
+    /// Files starting with `_` are partials, meant to be `@use`d rather than compiled alone
+    fn is_partial(path: &Path) -> bool {
+        path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('_'))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TestDir;
+
+    fn site(name: &str, files: &[(&str, &str)]) -> TestDir {
+        let dir = TestDir::new(&format!("sass-{}", name));
+        for (path, source) in files {
+            dir.write(&format!("sass/{}", path), source);
+        }
+        dir
+    }
+
+    #[test]
+    fn compiles_entry_points_but_not_partials() {
+        let dir = site("compile", &[("_vars.scss", "$w: 10px;"), ("blog/post.scss", "@use \"vars\";\n.a { width: vars.$w; }")]);
+
+        let urls = SassCompiler::compile_dir(&dir.join("sass"), &dir.join("out"), &["/assets/style.css"]).unwrap();
+        assert_eq!(urls, ["/assets/blog/post.css"]);
+        assert!(dir.read("out/assets/blog/post.css").contains("width: 10px"));
+    }
+
+    #[test]
+    fn refuses_to_overwrite_theme_stylesheet() {
+        let dir = site("collision", &[("style.scss", "body { color: red; }")]);
+
+        let error = SassCompiler::compile_dir(&dir.join("sass"), &dir.join("out"), &["/assets/style.css"]).unwrap_err();
+        assert!(error.to_string().contains("would overwrite the theme stylesheet /assets/style.css"));
+        assert!(!dir.path().join("out/assets/style.css").exists());
+    }
+
+    #[test]
+    fn errors_point_at_the_source_location() {
+        let dir = site("error", &[("main.scss", "a {\n  color: $missing;\n}")]);
+        let sass_dir = dir.path().join("sass");
+        let error = SassCompiler::compile(&sass_dir.join("main.scss"), &sass_dir).unwrap_err().to_string();
+        assert!(error.starts_with("Sass error in ") && error.contains("main.scss:2:"), "{}", error);
+    }
+}
@@ -0,0 +1,143 @@
+use anyhow::{anyhow, bail, Result};
+use serde_json::json;
+
+use crate::config::Config;
+use super::manifest::ThemeManifest;
+use super::Theme;
+
+/// Where the toggle script is written, relative to the output directory
+pub const TOGGLE_SCRIPT_URL: &str = "/assets/theme-toggle.js";
+
+/// Picks the reader's variant before the page paints and wires up the toggle button
+pub const TOGGLE_SCRIPT: &str = include_str!("../../themes/theme-toggle.js");
+
+/// A theme and color scheme readers can switch to
+#[derive(Debug, Clone)]
+pub struct ThemeVariant {
+    pub theme: Theme,
+    pub scheme: String,
+    /// Button text, naming only what differs between the variants
+    pub label: String,
+}
+
+/// The variants from `[theme_toggle]`. Pages are rendered in the first variant of their theme
+/// and the toggle script switches to the stored choice or the one matching `prefers-color-scheme`.
+#[derive(Debug, Clone)]
+pub struct ThemeToggle {
+    pub variants: Vec<ThemeVariant>,
+}
+
+impl ThemeToggle {
+    /// Parse `[theme_toggle] variants`, `None` when fewer than two are set. A variant without a
+    /// scheme uses the theme's default one. One variant must be in the site theme.
+    pub fn from_config(config: &Config, site_theme: &Theme) -> Result<Option<Self>> {
+        let entries = &config.theme_toggle.variants;
+        if entries.len() < 2 {
+            if !entries.is_empty() {
+                eprintln!("⚠️  [theme_toggle] needs at least two variants, the toggle is disabled");
+            }
+            return Ok(None);
+        }
+
+        let mut variants: Vec<ThemeVariant> = Vec::new();
+        for entry in entries {
+            let (name, scheme) = match entry.split_once(':') {
+                Some((name, scheme)) => (name, Some(scheme.trim())),
+                None => (entry.as_str(), None),
+            };
+            let theme = name.trim().parse::<Theme>().map_err(|error| anyhow!("Invalid [theme_toggle] variant '{}': {}", entry, error))?;
+            let manifest = ThemeManifest::load(&theme, &config.build.theme_dir)?;
+            let scheme = match scheme {
+                Some(scheme) => scheme.to_string(),
+                None => manifest.default_scheme(&config.theme_options).unwrap_or_else(|| "light".to_string()),
+            };
+            if !manifest.color_schemes.contains_key(&scheme) {
+                let schemes: Vec<&str> = manifest.color_schemes.keys().map(String::as_str).collect();
+                bail!(
+                    "Invalid [theme_toggle] variant '{}': theme {} has no color scheme '{}' (available: {})",
+                    entry,
+                    theme,
+                    scheme,
+                    schemes.join(", ")
+                );
+            }
+            if variants.iter().any(|variant| variant.theme == theme && variant.scheme == scheme) {
+                bail!("Duplicate [theme_toggle] variant '{}'", entry);
+            }
+            variants.push(ThemeVariant { theme, scheme, label: String::new() });
+        }
+
+        if !variants.iter().any(|variant| variant.theme == *site_theme) {
+            bail!("[theme_toggle] variants must include the site theme '{}'", site_theme);
+        }
+
+        let mut toggle = Self { variants };
+        toggle.set_labels();
+        Ok(Some(toggle))
+    }
+
+    /// Themes the toggle switches between, in the order listed
+    pub fn themes(&self) -> Vec<Theme> {
+        let mut themes: Vec<Theme> = Vec::new();
+        for variant in &self.variants {
+            if !themes.contains(&variant.theme) {
+                themes.push(variant.theme);
+            }
+        }
+        themes
+    }
+
+    /// Whether variants use different themes, which need their own stylesheet and markup
+    pub fn switches_themes(&self) -> bool {
+        self.themes().len() > 1
+    }
+
+    /// Color schemes used with a theme
+    pub fn schemes(&self, theme: &Theme) -> Vec<&str> {
+        self.variants
+            .iter()
+            .filter(|variant| variant.theme == *theme)
+            .map(|variant| variant.scheme.as_str())
+            .collect()
+    }
+
+    /// The variant pages in a theme are rendered in, `None` when the toggle doesn't offer the
+    /// theme and pages in it go without the toggle
+    pub fn default_variant(&self, theme: &Theme) -> Option<&ThemeVariant> {
+        self.variants.iter().find(|variant| variant.theme == *theme)
+    }
+
+    /// Variants as JSON for the toggle script
+    pub fn to_json(&self) -> String {
+        let variants: Vec<_> = self
+            .variants
+            .iter()
+            .map(|variant| json!({ "theme": variant.theme.to_string(), "scheme": variant.scheme, "label": variant.label }))
+            .collect();
+        serde_json::Value::Array(variants).to_string()
+    }
+
+    fn set_labels(&mut self) {
+        let one_theme = !self.switches_themes();
+        let one_variant_per_theme = self.themes().len() == self.variants.len();
+        for variant in &mut self.variants {
+            let theme = Self::capitalize(&variant.theme.to_string());
+            let scheme = Self::capitalize(&variant.scheme);
+            variant.label = if one_theme {
+                scheme
+            } else if one_variant_per_theme {
+                theme
+            } else {
+                format!("{} {}", theme, scheme)
+            };
+        }
+    }
+
+    fn capitalize(text: &str) -> String {
+        let mut chars = text.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use sha2::{Digest, Sha256, Sha384};
+
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub struct HashUtils;
+
+impl HashUtils {
+    /// Lowercase hex SHA-256 digest of `data`
+    pub fn sha256_hex(data: &[u8]) -> String {
+        Sha256::digest(data).iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    /// Subresource Integrity value (`sha384-<base64>`) for `data`
+    pub fn sri_sha384(data: &[u8]) -> String {
+        format!("sha384-{}", Self::base64(&Sha384::digest(data)))
+    }
+
+    /// Standard padded base64
+    fn base64(data: &[u8]) -> String {
+        let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+        for chunk in data.chunks(3) {
+            let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+            let group = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+            for index in 0..4 {
+                if index <= chunk.len() {
+                    encoded.push(BASE64_ALPHABET[(group >> (18 - 6 * index) & 0x3f) as usize] as char);
+                } else {
+                    encoded.push('=');
+                }
+            }
+        }
+        encoded
+    }
+}
@@ -0,0 +1,68 @@
+pub struct HtmlUtils;
+
+impl HtmlUtils {
+    /// Escape text for safe inclusion in HTML content and attribute values
+    pub fn escape(text: &str) -> String {
+        let mut escaped = String::with_capacity(text.len());
+        for ch in text.chars() {
+            match ch {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                '\'' => escaped.push_str("&#39;"),
+                _ => escaped.push(ch),
+            }
+        }
+        escaped
+    }
+
+    /// Rewrite relative `href`, `src` and `srcset` URLs to absolute ones. Root-relative URLs
+    /// are joined to `base_url`, document-relative ones to the directory of `page_url`.
+    pub fn absolutize_urls(html: &str, base_url: &str, page_url: &str) -> String {
+        let base_url = base_url.trim_end_matches('/');
+        let page_dir = page_url.rsplit_once('/').map_or("", |(directory, _)| directory);
+        let mut result = String::with_capacity(html.len());
+        let mut rest = html;
+
+        while let Some(pos) = rest.find("=\"") {
+            let (before, after) = rest.split_at(pos + 2);
+            result.push_str(before);
+            // Only attributes we know carry URLs
+            if before.ends_with(" href=\"") || before.ends_with(" src=\"") {
+                result.push_str(&Self::url_prefix(after, base_url, page_dir));
+            } else if before.ends_with(" srcset=\"")
+                && let Some(end) = after.find('"')
+            {
+                // Each candidate is "url [descriptor]"
+                let candidates: Vec<String> = after[..end]
+                    .split(',')
+                    .map(|candidate| {
+                        let candidate = candidate.trim();
+                        format!("{}{}", Self::url_prefix(candidate, base_url, page_dir), candidate)
+                    })
+                    .collect();
+                result.push_str(&candidates.join(", "));
+                rest = &after[end..];
+                continue;
+            }
+            rest = after;
+        }
+
+        result.push_str(rest);
+        result
+    }
+
+    /// What to put in front of `url` to make it absolute. Fragments, protocol-relative
+    /// `//host` and `scheme:` URLs are left alone.
+    fn url_prefix(url: &str, base_url: &str, page_dir: &str) -> String {
+        let has_scheme = url.split(['/', '"', '?', '#']).next().is_some_and(|part| part.contains(':'));
+        if url.starts_with("//") || url.starts_with(['#', '"']) || url.is_empty() || has_scheme {
+            String::new()
+        } else if url.starts_with('/') {
+            base_url.to_string()
+        } else {
+            format!("{}{}/", base_url, page_dir)
+        }
+    }
+}
@@ -1,3 +1,13 @@
 pub mod ascii_art;
+pub mod hash;
+pub mod html;
+pub mod slug;
+#[cfg(test)]
+pub mod test_dir;
 
-pub use ascii_art::AsciiArtGenerator;
\ No newline at end of file
+pub use ascii_art::AsciiArtGenerator;
+pub use hash::HashUtils;
+pub use html::HtmlUtils;
+pub use slug::SlugGenerator;
+#[cfg(test)]
+pub use test_dir::TestDir;
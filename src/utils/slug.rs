@@ -0,0 +1,24 @@
+pub struct SlugGenerator;
+
+impl SlugGenerator {
+    /// Convert text into a lowercase, hyphen-separated slug. Unicode letters and
+    /// digits are kept as-is; punctuation is dropped.
+    pub fn slugify(text: &str) -> String {
+        let mut slug = String::with_capacity(text.len());
+        let mut pending_hyphen = false;
+
+        for ch in text.chars().flat_map(char::to_lowercase) {
+            if ch.is_alphanumeric() {
+                if pending_hyphen && !slug.is_empty() {
+                    slug.push('-');
+                }
+                pending_hyphen = false;
+                slug.push(ch);
+            } else if ch.is_whitespace() || ch == '-' || ch == '_' {
+                pending_hyphen = true;
+            }
+        }
+
+        slug
+    }
+}
@@ -0,0 +1,50 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Scratch directory for a test, unique across parallel tests and removed when dropped
+pub struct TestDir {
+    path: PathBuf,
+}
+
+impl TestDir {
+    pub fn new(name: &str) -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "dodge-{}-{}-{}",
+            name,
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).expect("create test directory");
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// A path inside the directory, as a string the way paths are passed around the generator
+    pub fn join(&self, relative: &str) -> String {
+        self.path.join(relative).to_string_lossy().into_owned()
+    }
+
+    /// Write a file, creating its parent directories
+    pub fn write(&self, relative: &str, contents: impl AsRef<[u8]>) -> String {
+        let path = self.path.join(relative);
+        fs::create_dir_all(path.parent().expect("file has a parent")).expect("create parent directory");
+        fs::write(&path, contents).expect("write test file");
+        path.to_string_lossy().into_owned()
+    }
+
+    pub fn read(&self, relative: &str) -> String {
+        fs::read_to_string(self.path.join(relative)).expect("read test file")
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
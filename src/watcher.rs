@@ -0,0 +1,60 @@
+use glob::glob;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// How often watched paths are checked for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Polls files for changes, so rebuilding works the same on every platform without
+/// native file system events
+pub struct SiteWatcher {
+    pub paths: Vec<String>,
+}
+
+impl SiteWatcher {
+    pub fn new(paths: Vec<String>) -> Self {
+        Self { paths }
+    }
+
+    /// Call `rebuild` whenever a watched file is added, removed or modified. Never returns.
+    pub fn watch(&self, rebuild: impl Fn()) -> ! {
+        println!("👀 Watching {} for changes", self.paths.join(", "));
+        let mut previous = self.snapshot();
+        loop {
+            thread::sleep(POLL_INTERVAL);
+            let current = self.snapshot();
+            if current != previous {
+                // Keep the files as they were before the build, so edits saved while it runs
+                // differ from them at the next poll and trigger another one
+                previous = current;
+                rebuild();
+            }
+        }
+    }
+
+    /// Modification time of every file under the watched paths; missing paths are skipped
+    fn snapshot(&self) -> BTreeMap<PathBuf, SystemTime> {
+        let mut files = BTreeMap::new();
+        for path in &self.paths {
+            let root = Path::new(path);
+            if root.is_file() {
+                files.extend(Self::modified(root));
+                continue;
+            }
+            let Ok(entries) = glob(&format!("{}/**/*", path)) else {
+                continue;
+            };
+            for entry in entries.flatten().filter(|entry| entry.is_file()) {
+                files.extend(Self::modified(&entry));
+            }
+        }
+        files
+    }
+
+    fn modified(path: &Path) -> Option<(PathBuf, SystemTime)> {
+        let modified = path.metadata().and_then(|metadata| metadata.modified()).ok()?;
+        Some((path.to_path_buf(), modified))
+    }
+}
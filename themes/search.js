@@ -0,0 +1,109 @@
+// Dodge client-side search. Loads the build-time index and ranks pages
+// by where each query term appears: title, headings, tags, summary, body.
+(function () {
+  'use strict';
+
+  var root = document.querySelector('[data-search-index]');
+  if (!root) return;
+
+  var input = root.querySelector('input[type="search"]');
+  var status = root.querySelector('.search-status');
+  var results = root.querySelector('.search-results');
+  var pages = null;
+
+  var WEIGHTS = { title: 10, headings: 5, tags: 5, summary: 3, terms: 1 };
+
+  function escapeHtml(text) {
+    return text.replace(/[&<>"']/g, function (ch) {
+      return { '&': '&amp;', '<': '&lt;', '>': '&gt;', '"': '&quot;', "'": '&#39;' }[ch];
+    });
+  }
+
+  function tokenize(query) {
+    return query.toLowerCase().split(/[^\p{L}\p{N}]+/u).filter(Boolean);
+  }
+
+  function fields(page) {
+    return {
+      title: page.title.toLowerCase(),
+      headings: (page.headings || []).join(' ').toLowerCase(),
+      tags: (page.tags || []).join(' ').toLowerCase(),
+      summary: page.summary.toLowerCase(),
+      terms: page.terms
+    };
+  }
+
+  // Every term must match somewhere; the score adds up the weights of the fields it hits
+  function score(page, terms) {
+    var text = page._fields || (page._fields = fields(page));
+    var total = 0;
+    for (var i = 0; i < terms.length; i++) {
+      var hit = 0;
+      for (var field in WEIGHTS) {
+        if (text[field].indexOf(terms[i]) !== -1) hit += WEIGHTS[field];
+      }
+      if (!hit) return 0;
+      total += hit;
+    }
+    return total;
+  }
+
+  // Matches land at odd indexes when splitting on a capturing pattern
+  function highlight(text, terms) {
+    var pattern = new RegExp('(' + terms.map(function (term) {
+      return term.replace(/[.*+?^${}()|[\]\\]/g, '\\$&');
+    }).join('|') + ')', 'gi');
+    return text.split(pattern).map(function (part, i) {
+      return i % 2 ? '<mark>' + escapeHtml(part) + '</mark>' : escapeHtml(part);
+    }).join('');
+  }
+
+  function render(query) {
+    var terms = tokenize(query);
+    results.innerHTML = '';
+    if (!terms.length) {
+      status.textContent = '';
+      return;
+    }
+
+    var matches = pages
+      .map(function (page) { return { page: page, score: score(page, terms) }; })
+      .filter(function (match) { return match.score > 0; })
+      .sort(function (a, b) { return b.score - a.score; });
+
+    status.textContent = matches.length === 1 ? '1 result' : matches.length + ' results';
+    results.innerHTML = matches.map(function (match) {
+      var page = match.page;
+      return '<li class="search-result">' +
+        '<a href="' + escapeHtml(page.url) + '">' + highlight(page.title, terms) + '</a>' +
+        (page.date ? ' <time>' + escapeHtml(page.date) + '</time>' : '') +
+        '<p>' + highlight(page.summary, terms) + '</p>' +
+        '</li>';
+    }).join('');
+  }
+
+  function update() {
+    var query = input.value;
+    var url = new URL(window.location.href);
+    if (query) url.searchParams.set('q', query); else url.searchParams.delete('q');
+    window.history.replaceState(null, '', url);
+    render(query);
+  }
+
+  status.textContent = 'Loading…';
+  fetch(root.getAttribute('data-search-index'))
+    .then(function (response) { return response.json(); })
+    .then(function (index) {
+      pages = index;
+      input.value = new URLSearchParams(window.location.search).get('q') || '';
+      input.addEventListener('input', update);
+      input.form.addEventListener('submit', function (event) {
+        event.preventDefault();
+        update();
+      });
+      render(input.value);
+    })
+    .catch(function () {
+      status.textContent = 'Search is unavailable.';
+    });
+})();
@@ -0,0 +1,101 @@
+// Dodge theme toggle. Runs in <head> before the page paints: applies the variant the
+// reader picked last time, else the one matching prefers-color-scheme, else the one the
+// page was rendered in. The toggle button cycles through the variants.
+(function () {
+  'use strict';
+
+  var STORAGE_KEY = 'dodge-theme';
+  var root = document.documentElement;
+  var variants = JSON.parse(root.getAttribute('data-theme-variants') || '[]');
+  if (variants.length < 2) return;
+
+  var rendered = find((root.className.match(/\btheme-(\S+)/) || [])[1], root.getAttribute('data-color-scheme'));
+  var darkQuery = window.matchMedia ? window.matchMedia('(prefers-color-scheme: dark)') : null;
+  var current = null;
+
+  function id(variant) {
+    return variant.theme + ':' + variant.scheme;
+  }
+
+  function find(theme, scheme) {
+    for (var i = 0; i < variants.length; i++) {
+      if (variants[i].theme === theme && variants[i].scheme === scheme) return variants[i];
+    }
+    return null;
+  }
+
+  function stored() {
+    try {
+      var value = localStorage.getItem(STORAGE_KEY);
+      for (var i = 0; i < variants.length; i++) {
+        if (id(variants[i]) === value) return variants[i];
+      }
+    } catch (e) {
+      // Storage can be unavailable, e.g. with cookies blocked
+    }
+    return null;
+  }
+
+  function preferred() {
+    var choice = stored();
+    if (choice) return choice;
+    if (darkQuery) {
+      var scheme = darkQuery.matches ? 'dark' : 'light';
+      // Stay in the rendered theme when it has a variant for the preferred scheme
+      var match = find(rendered && rendered.theme, scheme);
+      for (var i = 0; !match && i < variants.length; i++) {
+        if (variants[i].scheme === scheme) match = variants[i];
+      }
+      if (match) return match;
+    }
+    return rendered || variants[0];
+  }
+
+  function next() {
+    return variants[(variants.indexOf(current) + 1) % variants.length];
+  }
+
+  function updateButton() {
+    var button = document.querySelector('.theme-toggle');
+    if (!button) return;
+    button.textContent = current.label;
+    button.title = 'Switch to ' + next().label;
+    button.setAttribute('aria-label', 'Theme: ' + current.label + '. Switch to ' + next().label);
+  }
+
+  function apply(variant) {
+    current = variant;
+    root.className = (root.className.replace(/\btheme-\S+/g, '') + ' theme-' + variant.theme).trim();
+    root.setAttribute('data-color-scheme', variant.scheme);
+    // Stylesheets of other themes are switched off rather than removed, so switching back is instant
+    var links = document.querySelectorAll('link[data-theme]');
+    for (var i = 0; i < links.length; i++) {
+      links[i].disabled = links[i].getAttribute('data-theme') !== variant.theme;
+    }
+    updateButton();
+  }
+
+  apply(preferred());
+
+  if (darkQuery && darkQuery.addEventListener) {
+    darkQuery.addEventListener('change', function () {
+      if (!stored()) apply(preferred());
+    });
+  }
+
+  document.addEventListener('DOMContentLoaded', function () {
+    var button = document.querySelector('.theme-toggle');
+    if (!button) return;
+    button.hidden = false;
+    updateButton();
+    button.addEventListener('click', function () {
+      var variant = next();
+      try {
+        localStorage.setItem(STORAGE_KEY, id(variant));
+      } catch (e) {
+        // The choice then only lasts until the page is left
+      }
+      apply(variant);
+    });
+  });
+})();